@@ -0,0 +1,170 @@
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::client::RestliClient;
+use crate::restli;
+
+pub const ROLES: &[&str] = &[
+    "ACCOUNT_BILLING_ADMIN",
+    "ACCOUNT_MANAGER",
+    "CAMPAIGN_MANAGER",
+    "CREATIVE_MANAGER",
+    "VIEWER",
+];
+/// Roles allowed to manage other users of an account.
+const ADMIN_ROLES: &[&str] = &["ACCOUNT_BILLING_ADMIN", "ACCOUNT_MANAGER"];
+const PAGE_SIZE: usize = 100;
+
+/// What `grant` will do (or did) on one account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Add,
+    Update,
+    Unchanged,
+}
+
+impl Change {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Add => "add",
+            Self::Update => "update",
+            Self::Unchanged => "unchanged",
+        }
+    }
+}
+
+/// `(account, role)` for every account of the authenticated member.
+pub fn my_accounts(client: &RestliClient) -> Result<Vec<(String, String)>> {
+    Ok(paged(client, "/adAccountUsers?q=authenticatedUser")?
+        .iter()
+        .filter_map(|r| {
+            Some((
+                r.get("account")?.as_str()?.to_string(),
+                r.get("role")?.as_str()?.to_string(),
+            ))
+        })
+        .collect())
+}
+
+/// Accounts of the authenticated member with their details: id, name, currency,
+/// status, type, reference (organization) and the member's role.
+pub fn account_summaries(client: &RestliClient) -> Result<Vec<Value>> {
+    let mut summaries = Vec::new();
+    for (account, role) in my_accounts(client)? {
+        let id = account.rsplit(':').next().unwrap_or(&account).to_string();
+        let details = client
+            .call(
+                "GET",
+                &format!("/adAccounts/{id}"),
+                &BTreeMap::new(),
+                &BTreeMap::new(),
+                None,
+            )?
+            .body;
+        let field = |name: &str| details.get(name).cloned().unwrap_or(Value::Null);
+        summaries.push(serde_json::json!({
+            "id": id,
+            "name": field("name"),
+            "currency": field("currency"),
+            "status": field("status"),
+            "type": field("type"),
+            "reference": field("reference"),
+            "role": role,
+        }));
+    }
+    Ok(summaries)
+}
+
+/// Currency code of an ad account (id or URN).
+pub fn account_currency(client: &RestliClient, account: &str) -> Result<String> {
+    let id = account.rsplit(':').next().unwrap_or(account);
+    let details = client
+        .call(
+            "GET",
+            &format!("/adAccounts/{id}"),
+            &BTreeMap::new(),
+            &BTreeMap::new(),
+            None,
+        )?
+        .body;
+    details
+        .get("currency")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("ad account {id} has no currency"))
+}
+
+/// Whether `role` may assign roles to other users (billing admin or account manager).
+pub fn can_manage(role: &str) -> bool {
+    ADMIN_ROLES.contains(&role)
+}
+
+/// Users (and their roles) of one account.
+pub fn account_users(client: &RestliClient, account: &str) -> Result<Vec<Value>> {
+    let account = restli::ensure_urn(account, "urn:li:sponsoredAccount");
+    paged(
+        client,
+        &format!(
+            "/adAccountUsers?q=accounts&accounts={}",
+            restli::escape(&account)
+        ),
+    )
+}
+
+/// Compare `user`'s current role on `account` with `role`.
+pub fn plan(client: &RestliClient, account: &str, user: &str, role: &str) -> Result<Change> {
+    let current = account_users(client, account)?
+        .into_iter()
+        .find(|u| u.get("user").and_then(|v| v.as_str()) == Some(user))
+        .and_then(|u| u.get("role")?.as_str().map(str::to_string));
+    Ok(match current.as_deref() {
+        None => Change::Add,
+        Some(current) if current == role => Change::Unchanged,
+        Some(_) => Change::Update,
+    })
+}
+
+/// Create or replace `user`'s role on `account` (PUT on the compound key).
+pub fn set_role(client: &RestliClient, account: &str, user: &str, role: &str) -> Result<()> {
+    if !ROLES.contains(&role) {
+        return Err(anyhow!("unknown role {role} ({})", ROLES.join(", ")));
+    }
+    let account = restli::ensure_urn(account, "urn:li:sponsoredAccount");
+    let path = format!(
+        "/adAccountUsers/(account:{},user:{})",
+        restli::escape(&account),
+        restli::escape(user)
+    );
+    let body = serde_json::json!({ "account": account, "user": user, "role": role });
+    client.call(
+        "PUT",
+        &path,
+        &BTreeMap::new(),
+        &BTreeMap::new(),
+        Some(&body),
+    )?;
+    Ok(())
+}
+
+/// Every element of a finder, paged with `start`/`count` (`path` already has a query).
+pub fn paged(client: &RestliClient, path: &str) -> Result<Vec<Value>> {
+    let mut items = Vec::new();
+    loop {
+        let page = format!("{path}&start={}&count={PAGE_SIZE}", items.len());
+        let resp = client.call("GET", &page, &BTreeMap::new(), &BTreeMap::new(), None)?;
+        let elements = resp
+            .body
+            .get("elements")
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+        let fetched = elements.len();
+        items.extend(elements);
+        let total = resp.body.pointer("/paging/total").and_then(|v| v.as_u64());
+        if fetched < PAGE_SIZE || total.is_some_and(|t| items.len() as u64 >= t) {
+            break;
+        }
+    }
+    Ok(items)
+}
@@ -46,6 +46,36 @@ pub fn upload_image(
     Ok(value.clone())
 }
 
+/// Images API upload: `/images?action=initializeUpload`, then PUT the bytes to the
+/// returned `uploadUrl`. Returns the initializeUpload value (`image`, `uploadUrl`,
+/// `uploadUrlExpiresAt`); the `urn:li:image` is usable once `/images/{urn}` is AVAILABLE.
+pub fn upload_image_api(client: &RestliClient, owner: &str, file: &FileParam) -> Result<Value> {
+    let req = serde_json::json!({
+        "initializeUploadRequest": { "owner": owner }
+    });
+
+    let mut query = BTreeMap::new();
+    query.insert("action".to_string(), "initializeUpload".to_string());
+    let resp = client.call("POST", "/images", &query, &BTreeMap::new(), Some(&req))?;
+
+    let value = resp
+        .body
+        .get("value")
+        .ok_or_else(|| anyhow!("missing response.value"))?;
+    let upload_url = value
+        .get("uploadUrl")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("missing uploadUrl"))?;
+    if value.get("image").and_then(|v| v.as_str()).is_none() {
+        return Err(anyhow!("missing image URN"));
+    }
+
+    let bytes = read_all_bytes(file)?;
+    client.put_bytes(upload_url, bytes, &BTreeMap::new(), true)?;
+
+    Ok(value.clone())
+}
+
 pub fn upload_video(
     client: &RestliClient,
     owner: &str,
@@ -98,10 +128,8 @@ pub fn upload_video(
         let bytes = read_all_bytes(file)?;
         client.put_bytes(&upload_url, bytes, &upload_headers, false)?;
 
-        if wait {
-            if let Some(asset) = value.get("asset").and_then(|v| v.as_str()) {
-                wait_for_asset_available(client, asset, Duration::from_secs(300))?;
-            }
+        if wait && let Some(asset) = value.get("asset").and_then(|v| v.as_str()) {
+            wait_for_asset_available(client, asset, Duration::from_secs(300))?;
         }
 
         return Ok(value.clone());
@@ -182,10 +210,8 @@ pub fn upload_video(
         Some(&complete),
     )?;
 
-    if wait {
-        if let Some(asset) = value.get("asset").and_then(|v| v.as_str()) {
-            wait_for_asset_available(client, asset, Duration::from_secs(300))?;
-        }
+    if wait && let Some(asset) = value.get("asset").and_then(|v| v.as_str()) {
+        wait_for_asset_available(client, asset, Duration::from_secs(300))?;
     }
 
     Ok(serde_json::json!({
@@ -194,6 +220,174 @@ pub fn upload_video(
     }))
 }
 
+/// Extra uploads requested at `initializeUpload` time; they are PUT to the returned
+/// `captionsUploadUrl` / `thumbnailUploadUrl` before finalizing.
+#[derive(Debug, Default, Clone)]
+pub struct VideoUploadOptions {
+    /// SRT captions (already validated).
+    pub captions: Option<Vec<u8>>,
+    /// JPG/PNG thumbnail (already validated).
+    pub thumbnail: Option<Vec<u8>>,
+    /// Poll `/videos/{urn}` until AVAILABLE.
+    pub wait: bool,
+}
+
+/// Videos API upload: `initializeUpload` with the file size, PUT each returned byte
+/// range and any captions or thumbnail, then `finalizeUpload` with the parts' ETags.
+/// Returns the initializeUpload value (without `uploadInstructions`) plus
+/// `uploadedPartIds`, and the processed video under `processing` when waiting.
+pub fn upload_video_api(
+    client: &RestliClient,
+    owner: &str,
+    file: &FileParam,
+    opts: VideoUploadOptions,
+) -> Result<Value> {
+    let file_size = std::fs::metadata(&file.path)
+        .with_context(|| format!("stat {}", file.path.display()))?
+        .len();
+    let init = serde_json::json!({
+        "initializeUploadRequest": {
+            "owner": owner,
+            "fileSizeBytes": file_size,
+            "uploadCaptions": opts.captions.is_some(),
+            "uploadThumbnail": opts.thumbnail.is_some()
+        }
+    });
+
+    let mut query = BTreeMap::new();
+    query.insert("action".to_string(), "initializeUpload".to_string());
+    let resp = client.call("POST", "/videos", &query, &BTreeMap::new(), Some(&init))?;
+    let mut value = resp
+        .body
+        .get("value")
+        .cloned()
+        .ok_or_else(|| anyhow!("missing response.value"))?;
+    let video = value
+        .get("video")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("missing video URN"))?
+        .to_string();
+    let instructions = value
+        .get("uploadInstructions")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| anyhow!("missing uploadInstructions"))?;
+
+    let mut f = File::open(&file.path).with_context(|| format!("open {}", file.path.display()))?;
+    let mut part_ids = Vec::new();
+    for (i, instruction) in instructions.iter().enumerate() {
+        let url = instruction
+            .get("uploadUrl")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow!("missing uploadUrl in instruction {i}"))?;
+        let first = instruction
+            .get("firstByte")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| anyhow!("missing firstByte in instruction {i}"))?;
+        let last = instruction
+            .get("lastByte")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| anyhow!("missing lastByte in instruction {i}"))?;
+        if last < first || last >= file_size {
+            return Err(anyhow!(
+                "instruction {i} range {first}-{last} is outside the file ({file_size} bytes)"
+            ));
+        }
+
+        let mut buf = vec![0u8; (last - first + 1) as usize];
+        f.seek(SeekFrom::Start(first)).context("seek part")?;
+        f.read_exact(&mut buf).context("read part")?;
+
+        log::debug!(
+            "upload part {}/{} ({first}-{last})",
+            i + 1,
+            instructions.len()
+        );
+        let put_resp = client.put_bytes(url, buf, &BTreeMap::new(), false)?;
+        let etag = find_header_ci(&put_resp.headers, "etag")
+            .ok_or_else(|| anyhow!("missing ETag header for part {i}"))?;
+        part_ids.push(etag.trim_matches('"').to_string());
+    }
+
+    for (data, key) in [
+        (opts.captions, "captionsUploadUrl"),
+        (opts.thumbnail, "thumbnailUploadUrl"),
+    ] {
+        let Some(data) = data else { continue };
+        let url = value
+            .get(key)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow!("missing {key}"))?;
+        client.put_bytes(url, data, &BTreeMap::new(), false)?;
+    }
+
+    let finalize = serde_json::json!({
+        "finalizeUploadRequest": {
+            "video": video,
+            "uploadToken": value.get("uploadToken").and_then(|v| v.as_str()).unwrap_or(""),
+            "uploadedPartIds": part_ids
+        }
+    });
+    let mut finalize_query = BTreeMap::new();
+    finalize_query.insert("action".to_string(), "finalizeUpload".to_string());
+    client.call(
+        "POST",
+        "/videos",
+        &finalize_query,
+        &BTreeMap::new(),
+        Some(&finalize),
+    )?;
+
+    if let Value::Object(map) = &mut value {
+        map.remove("uploadInstructions");
+        map.insert("uploadedPartIds".to_string(), serde_json::json!(part_ids));
+        if opts.wait {
+            let processed = wait_available(
+                client,
+                &format!("/videos/{}", urlencoding::encode(&video)),
+                &format!("video {video}"),
+                Duration::from_secs(600),
+            )?;
+            map.insert("processing".to_string(), processed);
+        }
+    }
+    Ok(value)
+}
+
+/// Poll `path` (a `/videos` or `/documents` entity) until its status is AVAILABLE;
+/// PROCESSING_FAILED is an error carrying `processingFailureReason`. `label` names the
+/// resource in errors, e.g. `video urn:li:video:1`.
+pub fn wait_available(
+    client: &RestliClient,
+    path: &str,
+    label: &str,
+    timeout: Duration,
+) -> Result<Value> {
+    let start = Instant::now();
+    loop {
+        let resp = client.call("GET", path, &BTreeMap::new(), &BTreeMap::new(), None)?;
+        match resp.body.get("status").and_then(|v| v.as_str()) {
+            Some("AVAILABLE") => return Ok(resp.body),
+            Some("PROCESSING_FAILED") => {
+                let reason = resp
+                    .body
+                    .get("processingFailureReason")
+                    .map(|v| {
+                        v.as_str()
+                            .map(str::to_string)
+                            .unwrap_or_else(|| v.to_string())
+                    })
+                    .unwrap_or_else(|| "no reason given".to_string());
+                return Err(anyhow!("{label} processing failed: {reason}"));
+            }
+            _ => {}
+        }
+        if start.elapsed() >= timeout {
+            return Err(anyhow!("{label} processing timeout"));
+        }
+        sleep(Duration::from_secs(3));
+    }
+}
+
 fn extract_http_upload(value: &Value) -> Result<(String, BTreeMap<String, String>)> {
     let http = value
         .get("uploadMechanism")
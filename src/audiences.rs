@@ -0,0 +1,1036 @@
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crate::client::RestliClient;
+use crate::hashing::{hash_email, is_sha256_hex, sha256_hex};
+use crate::profile;
+use crate::records::{ColumnMapping, Record, ReportWriter};
+use crate::restli;
+
+pub const DEFAULT_BATCH_SIZE: usize = 5000;
+pub const SEGMENT_URN: &str = "urn:li:dmpSegment";
+pub const REPORT_COLUMNS: &[&str] = &["row", "key", "action", "status", "http_status", "error"];
+
+pub const USER_FIELDS: &[&str] = &[
+    "email",
+    "sha256_email",
+    "first_name",
+    "last_name",
+    "title",
+    "company",
+    "country",
+];
+
+pub const COMPANY_FIELDS: &[&str] = &[
+    "company_name",
+    "organization",
+    "website",
+    "email_domain",
+    "page_url",
+    "stock_symbol",
+    "industry",
+    "city",
+    "state",
+    "country",
+    "postal_code",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentKind {
+    User,
+    Company,
+}
+
+impl SegmentKind {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "user" => Ok(Self::User),
+            "company" => Ok(Self::Company),
+            other => Err(anyhow!(
+                "invalid segment type {other} (expected: user|company)"
+            )),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Company => "company",
+        }
+    }
+
+    pub fn fields(self) -> &'static [&'static str] {
+        match self {
+            Self::User => USER_FIELDS,
+            Self::Company => COMPANY_FIELDS,
+        }
+    }
+
+    /// Members endpoint for a segment (numeric id or `urn:li:dmpSegment:...`).
+    pub fn members_path(self, segment: &str) -> String {
+        let collection = match self {
+            Self::User => "users",
+            Self::Company => "companies",
+        };
+        format!("/dmpSegments/{}/{}", segment_id(segment), collection)
+    }
+}
+
+pub fn segment_id(segment: &str) -> &str {
+    segment.rsplit(':').next().unwrap_or(segment)
+}
+
+/// A normalized segment member (without `action`). `row` is `None` for members that
+/// only exist in a sync snapshot.
+#[derive(Debug, Clone)]
+pub struct Member {
+    pub row: Option<usize>,
+    pub key: String,
+    pub element: Value,
+}
+
+pub struct UploadOptions {
+    pub segment: String,
+    pub kind: SegmentKind,
+    pub action: String,
+    pub batch_size: usize,
+    pub dry_run: bool,
+}
+
+/// Normalize rows into members. Rows that cannot be matched and duplicates are
+/// reported as skipped.
+pub fn collect_members(
+    kind: SegmentKind,
+    records: &[Record],
+    mapping: &ColumnMapping,
+    action: &str,
+    report: &mut ReportWriter,
+) -> Result<(Vec<Member>, u64)> {
+    let mut seen = HashSet::new();
+    let mut members = Vec::new();
+    let mut skipped = 0u64;
+    for record in records {
+        let member = match kind {
+            SegmentKind::User => user_member(record, mapping),
+            SegmentKind::Company => company_member(record, mapping),
+        };
+        let (key, reason) = match member {
+            Ok(element) => {
+                let key = member_key(kind, &element);
+                if seen.insert(key.clone()) {
+                    members.push(Member {
+                        row: Some(record.row),
+                        key,
+                        element,
+                    });
+                    continue;
+                }
+                (Some(key), "duplicate row".to_string())
+            }
+            Err(reason) => (None, reason),
+        };
+        skipped += 1;
+        write_row(
+            report,
+            Some(record.row),
+            key.as_deref(),
+            action,
+            "skipped",
+            None,
+            Some(&reason),
+        )?;
+    }
+    Ok((members, skipped))
+}
+
+/// Upload members to the segment in `BATCH_CREATE` chunks, one report line per row.
+pub fn upload(
+    client: Option<&RestliClient>,
+    records: &[Record],
+    mapping: &ColumnMapping,
+    opts: &UploadOptions,
+    report: &mut ReportWriter,
+) -> Result<Value> {
+    let (members, skipped) = collect_members(opts.kind, records, mapping, &opts.action, report)?;
+    let members: Vec<&Member> = members.iter().collect();
+
+    let mut counts = BatchCounts::default();
+    if opts.dry_run {
+        report_dry_run(report, &opts.action, &members)?;
+    } else {
+        let client = client.ok_or_else(|| anyhow!("client required unless --dry-run"))?;
+        send_members(
+            client,
+            &opts.segment,
+            opts.kind,
+            &opts.action,
+            &members,
+            opts.batch_size,
+            report,
+            &mut counts,
+            |_| Ok(()),
+        )?;
+    }
+
+    Ok(serde_json::json!({
+        "segment": restli::ensure_urn(&opts.segment, SEGMENT_URN),
+        "rows": records.len(),
+        "members": members.len(),
+        "batches": counts.batches,
+        "succeeded": counts.succeeded,
+        "failed": counts.failed,
+        "skipped": skipped,
+        "dry_run": opts.dry_run,
+    }))
+}
+
+/// Members last sent to a segment, keyed by [`member_key`] (a hash). Each member keeps
+/// only what a REMOVE needs to name it (see [`snapshot_element`]); entries written by
+/// older versions may be `null` and cannot be removed.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Snapshot {
+    pub segment: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub members: BTreeMap<String, Value>,
+}
+
+impl Snapshot {
+    /// Load the snapshot at `path` (or start an empty one) and replay the journal an
+    /// interrupted sync left behind.
+    pub fn load(path: &Path, segment: &str, kind: SegmentKind) -> Result<Self> {
+        let segment = restli::ensure_urn(segment, SEGMENT_URN);
+        let mut snapshot = if path.exists() {
+            let raw =
+                fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+            let snapshot: Self = serde_json::from_str(&raw)
+                .with_context(|| format!("invalid snapshot {}", path.display()))?;
+            if snapshot.segment != segment || snapshot.kind != kind.as_str() {
+                return Err(anyhow!(
+                    "snapshot {} is for {} ({}), not {segment} ({})",
+                    path.display(),
+                    snapshot.segment,
+                    snapshot.kind,
+                    kind.as_str()
+                ));
+            }
+            snapshot
+        } else {
+            Self {
+                segment,
+                kind: kind.as_str().to_string(),
+                members: BTreeMap::new(),
+            }
+        };
+        // Older snapshots stored whole elements under keys that covered every field.
+        let members = std::mem::take(&mut snapshot.members);
+        for (key, element) in members {
+            let element = snapshot_element(kind, &element);
+            let key = if element.is_null() {
+                key
+            } else {
+                member_key(kind, &element)
+            };
+            snapshot.members.insert(key, element);
+        }
+        snapshot.replay_journal(&journal_path(path))?;
+        Ok(snapshot)
+    }
+
+    fn replay_journal(&mut self, journal: &Path) -> Result<()> {
+        if !journal.exists() {
+            return Ok(());
+        }
+        let raw =
+            fs::read_to_string(journal).with_context(|| format!("read {}", journal.display()))?;
+        let mut replayed = 0;
+        for line in raw.lines().filter(|l| !l.trim().is_empty()) {
+            // A torn last line is a batch whose entry was never completed; LinkedIn may
+            // have accepted it, and the next sync simply re-sends it.
+            let Ok(entry) = serde_json::from_str::<Value>(line) else {
+                log::warn!("ignoring incomplete journal entry in {}", journal.display());
+                break;
+            };
+            let Some(key) = entry.get("key").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            match entry.get("op").and_then(|v| v.as_str()) {
+                Some("add") => {
+                    let element = entry.get("element").cloned().unwrap_or(Value::Null);
+                    self.members.insert(key.to_string(), element);
+                }
+                Some("remove") => {
+                    self.members.remove(key);
+                }
+                _ => continue,
+            }
+            replayed += 1;
+        }
+        log::info!(
+            "resumed {replayed} journaled changes from {}",
+            journal.display()
+        );
+        Ok(())
+    }
+
+    /// Write via a temp file + rename so an interrupted sync never leaves a torn
+    /// snapshot; the journal is folded in, so it is removed afterwards.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec(self)?)
+            .with_context(|| format!("write {}", tmp.display()))?;
+        fs::rename(&tmp, path).with_context(|| format!("write {}", path.display()))?;
+        let journal = journal_path(path);
+        if journal.exists() {
+            fs::remove_file(&journal).with_context(|| format!("remove {}", journal.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// `<snapshot>.journal`: NDJSON of the changes accepted since the snapshot was saved.
+fn journal_path(snapshot: &Path) -> PathBuf {
+    let mut path = snapshot.as_os_str().to_owned();
+    path.push(".journal");
+    PathBuf::from(path)
+}
+
+/// Append-only log of accepted batches, so checkpointing costs one line per member
+/// instead of rewriting the whole snapshot after every batch.
+struct Journal {
+    file: fs::File,
+}
+
+impl Journal {
+    fn open(snapshot: &Path) -> Result<Self> {
+        let path = journal_path(snapshot);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+        }
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("open {}", path.display()))?;
+        Ok(Self { file })
+    }
+
+    fn append(&mut self, entries: &[Value]) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let mut buf = Vec::new();
+        for entry in entries {
+            serde_json::to_writer(&mut buf, entry)?;
+            buf.push(b'\n');
+        }
+        self.file
+            .write_all(&buf)
+            .context("write snapshot journal")?;
+        self.file.sync_data().context("sync snapshot journal")?;
+        Ok(())
+    }
+}
+
+/// Default snapshot location: `<config dir>/snapshots/dmpSegment-<id>-<type>.json`.
+pub fn default_snapshot_path(segment: &str, kind: SegmentKind) -> Option<PathBuf> {
+    profile::config_dir().map(|dir| {
+        dir.join("snapshots").join(format!(
+            "dmpSegment-{}-{}.json",
+            segment_id(segment),
+            kind.as_str()
+        ))
+    })
+}
+
+pub struct SyncOptions {
+    pub segment: String,
+    pub kind: SegmentKind,
+    pub batch_size: usize,
+    pub dry_run: bool,
+}
+
+/// Send only the difference between `records` and the snapshot: ADD for new members,
+/// REMOVE for members that disappeared. Accepted batches are journaled as they go and
+/// the snapshot is saved at the end, so a failed or interrupted sync resumes from what
+/// LinkedIn already accepted.
+pub fn sync(
+    client: Option<&RestliClient>,
+    records: &[Record],
+    mapping: &ColumnMapping,
+    opts: &SyncOptions,
+    snapshot: &mut Snapshot,
+    snapshot_path: &Path,
+    report: &mut ReportWriter,
+) -> Result<Value> {
+    let (members, skipped) = collect_members(opts.kind, records, mapping, "ADD", report)?;
+    // An empty result usually means a wrong mapping, not an intentionally empty list.
+    if members.is_empty() && !snapshot.members.is_empty() {
+        return Err(anyhow!(
+            "no valid rows in input; refusing to remove all {} members of the segment",
+            snapshot.members.len()
+        ));
+    }
+    let current: HashSet<&str> = members.iter().map(|m| m.key.as_str()).collect();
+
+    let adds: Vec<&Member> = members
+        .iter()
+        .filter(|m| !snapshot.members.contains_key(&m.key))
+        .collect();
+    let stale: Vec<Member> = snapshot
+        .members
+        .iter()
+        .filter(|(key, _)| !current.contains(key.as_str()))
+        .map(|(key, element)| Member {
+            row: None,
+            key: key.clone(),
+            element: element.clone(),
+        })
+        .collect();
+    // Entries from older snapshots may hold nothing a REMOVE could name.
+    let (removes, unremovable): (Vec<&Member>, Vec<&Member>) =
+        stale.iter().partition(|m| !m.element.is_null());
+    for member in &unremovable {
+        write_row(
+            report,
+            None,
+            Some(&member.key),
+            "REMOVE",
+            "skipped",
+            None,
+            Some("snapshot entry from an older version has no fields to remove it by"),
+        )?;
+    }
+    let unchanged = members.len() - adds.len();
+
+    let mut added = BatchCounts::default();
+    let mut removed = BatchCounts::default();
+    if opts.dry_run {
+        report_dry_run(report, "ADD", &adds)?;
+        report_dry_run(report, "REMOVE", &removes)?;
+    } else {
+        let client = client.ok_or_else(|| anyhow!("client required unless --dry-run"))?;
+        let mut journal = Journal::open(snapshot_path)?;
+        send_members(
+            client,
+            &opts.segment,
+            opts.kind,
+            "ADD",
+            &adds,
+            opts.batch_size,
+            report,
+            &mut added,
+            |accepted| {
+                let mut entries = Vec::new();
+                for member in accepted {
+                    let element = snapshot_element(opts.kind, &member.element);
+                    entries.push(serde_json::json!({
+                        "op": "add",
+                        "key": member.key,
+                        "element": element,
+                    }));
+                    snapshot.members.insert(member.key.clone(), element);
+                }
+                journal.append(&entries)
+            },
+        )?;
+        send_members(
+            client,
+            &opts.segment,
+            opts.kind,
+            "REMOVE",
+            &removes,
+            opts.batch_size,
+            report,
+            &mut removed,
+            |accepted| {
+                let mut entries = Vec::new();
+                for member in accepted {
+                    entries.push(serde_json::json!({ "op": "remove", "key": member.key }));
+                    snapshot.members.remove(&member.key);
+                }
+                journal.append(&entries)
+            },
+        )?;
+        snapshot.save(snapshot_path)?;
+    }
+
+    Ok(serde_json::json!({
+        "segment": restli::ensure_urn(&opts.segment, SEGMENT_URN),
+        "rows": records.len(),
+        "members": members.len(),
+        "unchanged": unchanged,
+        "add": { "pending": adds.len(), "succeeded": added.succeeded, "failed": added.failed },
+        "remove": {
+            "pending": removes.len(),
+            "succeeded": removed.succeeded,
+            "failed": removed.failed,
+            "unremovable": unremovable.len(),
+        },
+        "batches": added.batches + removed.batches,
+        "skipped": skipped,
+        "snapshot": snapshot_path.display().to_string(),
+        "dry_run": opts.dry_run,
+    }))
+}
+
+/// Fields a user member without an email is matched (and removed) by.
+const USER_MATCH_FIELDS: &[&str] = &["firstName", "lastName", "title", "company", "country"];
+/// Fields a company is matched by; location, industry and stock symbol only help
+/// LinkedIn's matching and are not part of the member's identity.
+const COMPANY_MATCH_FIELDS: &[&str] = &[
+    "companyName",
+    "organizationUrn",
+    "companyWebsiteDomain",
+    "companyEmailDomain",
+    "companyPageUrl",
+];
+
+/// What the snapshot keeps for a member, enough to send it in a REMOVE: the hashed
+/// `userIds` when the user has an email, otherwise its match fields.
+fn snapshot_element(kind: SegmentKind, element: &Value) -> Value {
+    if let Some(ids) = element.get("userIds") {
+        return serde_json::json!({ "userIds": ids });
+    }
+    let fields = match kind {
+        SegmentKind::User => USER_MATCH_FIELDS,
+        SegmentKind::Company => COMPANY_MATCH_FIELDS,
+    };
+    let kept: serde_json::Map<String, Value> = fields
+        .iter()
+        .filter_map(|field| Some((field.to_string(), element.get(*field)?.clone())))
+        .collect();
+    if kept.is_empty() {
+        Value::Null
+    } else {
+        Value::Object(kept)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct BatchCounts {
+    pub batches: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+}
+
+/// Send members with `action` in chunks, one report line per member. `on_sent`
+/// receives the members of each chunk that LinkedIn accepted.
+#[allow(clippy::too_many_arguments)]
+pub fn send_members(
+    client: &RestliClient,
+    segment: &str,
+    kind: SegmentKind,
+    action: &str,
+    members: &[&Member],
+    batch_size: usize,
+    report: &mut ReportWriter,
+    counts: &mut BatchCounts,
+    mut on_sent: impl FnMut(&[&Member]) -> Result<()>,
+) -> Result<()> {
+    let path = kind.members_path(segment);
+    for chunk in members.chunks(batch_size.max(1)) {
+        counts.batches += 1;
+        let elements: Vec<Value> = chunk
+            .iter()
+            .map(|member| {
+                let mut element = member.element.clone();
+                element["action"] = Value::String(action.to_string());
+                element
+            })
+            .collect();
+
+        let mut accepted = Vec::new();
+        match client.batch_create(&path, elements) {
+            Ok(results) => {
+                for (member, result) in chunk.iter().zip(&results) {
+                    if result.is_success() {
+                        counts.succeeded += 1;
+                        accepted.push(*member);
+                    } else {
+                        counts.failed += 1;
+                    }
+                    write_row(
+                        report,
+                        member.row,
+                        Some(&member.key),
+                        action,
+                        if result.is_success() { "ok" } else { "failed" },
+                        Some(result.status as u64),
+                        result.error.as_deref(),
+                    )?;
+                }
+            }
+            Err(err) => {
+                log::warn!("segment batch {} failed: {err}", counts.batches);
+                for member in chunk {
+                    counts.failed += 1;
+                    write_row(
+                        report,
+                        member.row,
+                        Some(&member.key),
+                        action,
+                        "failed",
+                        None,
+                        Some(&err.to_string()),
+                    )?;
+                }
+            }
+        }
+        on_sent(&accepted)?;
+    }
+    Ok(())
+}
+
+fn report_dry_run(report: &mut ReportWriter, action: &str, members: &[&Member]) -> Result<()> {
+    for member in members {
+        write_row(
+            report,
+            member.row,
+            Some(&member.key),
+            action,
+            "dry-run",
+            None,
+            None,
+        )?;
+    }
+    Ok(())
+}
+
+/// Stable identity of a member: the email hash for users that have one, otherwise
+/// SHA-256 of its match fields (company names compared case-insensitively), so
+/// editing e.g. a company's city does not turn it into a new member.
+pub fn member_key(kind: SegmentKind, element: &Value) -> String {
+    if let Some(hash) = element
+        .pointer("/userIds/0/idValue")
+        .and_then(|v| v.as_str())
+    {
+        return hash.to_string();
+    }
+    let mut identity = snapshot_element(kind, element);
+    if let Some(name) = identity.get_mut("companyName")
+        && let Some(lower) = name.as_str().map(str::to_lowercase)
+    {
+        *name = Value::String(lower);
+    }
+    sha256_hex(&identity.to_string())
+}
+
+fn user_member(record: &Record, mapping: &ColumnMapping) -> std::result::Result<Value, String> {
+    let mut member = serde_json::Map::new();
+    let email_hash = if let Some(email) = mapping.get(record, "email") {
+        Some(hash_email(email).ok_or_else(|| format!("invalid email: {email}"))?)
+    } else if let Some(hashed) = mapping.get(record, "sha256_email") {
+        let hashed = hashed.trim();
+        if !is_sha256_hex(hashed) {
+            return Err(format!(
+                "invalid sha256_email (expected 64 hex chars): {hashed}"
+            ));
+        }
+        Some(hashed.to_ascii_lowercase())
+    } else {
+        None
+    };
+    if let Some(hash) = email_hash {
+        member.insert(
+            "userIds".to_string(),
+            serde_json::json!([{ "idType": "SHA256_EMAIL", "idValue": hash }]),
+        );
+    }
+
+    let first_name = mapping.get(record, "first_name");
+    let last_name = mapping.get(record, "last_name");
+    if !member.contains_key("userIds") && (first_name.is_none() || last_name.is_none()) {
+        return Err("no email or first+last name".to_string());
+    }
+    for (field, key) in [
+        ("first_name", "firstName"),
+        ("last_name", "lastName"),
+        ("title", "title"),
+        ("company", "company"),
+    ] {
+        if let Some(value) = mapping.get(record, field) {
+            member.insert(key.to_string(), Value::String(collapse_whitespace(value)));
+        }
+    }
+    if let Some(country) = mapping.get(record, "country") {
+        member.insert(
+            "country".to_string(),
+            Value::String(country.to_ascii_uppercase()),
+        );
+    }
+    Ok(Value::Object(member))
+}
+
+/// Company matching fields are normalized locally; LinkedIn matches them in clear text.
+fn company_member(record: &Record, mapping: &ColumnMapping) -> std::result::Result<Value, String> {
+    let mut member = serde_json::Map::new();
+    let mut insert = |key: &str, value: String| {
+        if !value.is_empty() {
+            member.insert(key.to_string(), Value::String(value));
+        }
+    };
+
+    if let Some(v) = mapping.get(record, "company_name") {
+        insert("companyName", collapse_whitespace(v));
+    }
+    if let Some(v) = mapping.get(record, "organization") {
+        insert(
+            "organizationUrn",
+            restli::ensure_urn(v, "urn:li:organization"),
+        );
+    }
+    if let Some(v) = mapping.get(record, "website") {
+        insert("companyWebsiteDomain", normalize_domain(v));
+    }
+    if let Some(v) = mapping.get(record, "email_domain") {
+        insert("companyEmailDomain", normalize_domain(v));
+    }
+    if let Some(v) = mapping.get(record, "page_url") {
+        insert("companyPageUrl", normalize_page_url(v));
+    }
+    if let Some(v) = mapping.get(record, "stock_symbol") {
+        insert("stockSymbol", v.to_ascii_uppercase());
+    }
+    for (field, key) in [
+        ("city", "city"),
+        ("state", "state"),
+        ("postal_code", "postalCode"),
+    ] {
+        if let Some(v) = mapping.get(record, field) {
+            insert(key, collapse_whitespace(v));
+        }
+    }
+    if let Some(v) = mapping.get(record, "country") {
+        insert("country", v.to_ascii_uppercase());
+    }
+
+    if !COMPANY_MATCH_FIELDS.iter().any(|k| member.contains_key(*k)) {
+        return Err("no company name, organization, domain or page url".to_string());
+    }
+    if let Some(v) = mapping.get(record, "industry") {
+        member.insert(
+            "industries".to_string(),
+            serde_json::json!([collapse_whitespace(v)]),
+        );
+    }
+    Ok(Value::Object(member))
+}
+
+fn collapse_whitespace(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `https://www.Example.com/about` or `jane@example.com` -> `example.com`.
+fn normalize_domain(value: &str) -> String {
+    let value = value.trim().to_ascii_lowercase();
+    let value = value.rsplit('@').next().unwrap_or(&value);
+    let value = value
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_start_matches("www.");
+    let host = value.split(['/', '?', '#']).next().unwrap_or(value);
+    host.split(':')
+        .next()
+        .unwrap_or(host)
+        .trim_end_matches('.')
+        .to_string()
+}
+
+/// Canonicalize LinkedIn company page URLs to `https://www.linkedin.com/company/<slug>`.
+fn normalize_page_url(value: &str) -> String {
+    let trimmed = value.trim();
+    let lower = trimmed.to_ascii_lowercase();
+    if let Some(i) = lower.find("linkedin.com/company/") {
+        let slug = trimmed[i + "linkedin.com/company/".len()..]
+            .split(['/', '?', '#'])
+            .next()
+            .unwrap_or("");
+        if !slug.is_empty() {
+            return format!("https://www.linkedin.com/company/{slug}");
+        }
+    }
+    trimmed.to_string()
+}
+
+/// Summarize a segment's build status and match rate.
+pub fn segment_status(client: &RestliClient, segment: &str) -> Result<Value> {
+    let resp = client.call(
+        "GET",
+        &format!("/dmpSegments/{}", segment_id(segment)),
+        &BTreeMap::new(),
+        &BTreeMap::new(),
+        None,
+    )?;
+    let body = resp.body;
+    let destination = body
+        .get("destinations")
+        .and_then(|v| v.as_array())
+        .and_then(|d| d.first());
+    let field = |name: &str| {
+        body.get(name)
+            .or_else(|| destination.and_then(|d| d.get(name)))
+            .cloned()
+    };
+
+    let status = field("status")
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_else(|| "UNKNOWN".to_string());
+    let matched = field("matchedCount").and_then(|v| v.as_u64());
+    let input = field("inputCount").and_then(|v| v.as_u64());
+    let match_rate = match (matched, input) {
+        (Some(m), Some(i)) if i > 0 => Some(m as f64 / i as f64),
+        _ => None,
+    };
+    let ready = status == "READY";
+
+    Ok(serde_json::json!({
+        "segment": restli::ensure_urn(segment, SEGMENT_URN),
+        "name": body.get("name"),
+        "type": body.get("type"),
+        "status": status,
+        "ready": ready,
+        "matched_count": matched,
+        "input_count": input,
+        "audience_size": field("audienceSize"),
+        "match_rate": match_rate,
+        "destinations": body.get("destinations"),
+    }))
+}
+
+/// Poll until the segment is READY (or FAILED / timeout).
+pub fn wait_until_ready(
+    client: &RestliClient,
+    segment: &str,
+    timeout: Duration,
+    interval: Duration,
+) -> Result<Value> {
+    let start = Instant::now();
+    loop {
+        let status = segment_status(client, segment)?;
+        if status["ready"].as_bool().unwrap_or(false) {
+            return Ok(status);
+        }
+        if status["status"].as_str() == Some("FAILED") {
+            return Err(anyhow!("segment build failed: {status}"));
+        }
+        if start.elapsed() >= timeout {
+            return Err(anyhow!("segment not ready before timeout: {status}"));
+        }
+        sleep(interval);
+    }
+}
+
+fn write_row(
+    report: &mut ReportWriter,
+    row: Option<usize>,
+    key: Option<&str>,
+    action: &str,
+    status: &str,
+    http_status: Option<u64>,
+    error: Option<&str>,
+) -> Result<()> {
+    report.write(&serde_json::json!({
+        "row": row,
+        "key": key,
+        "action": action,
+        "status": status,
+        "http_status": http_status,
+        "error": error,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::TunnelMode;
+    use serde_json::json;
+    use std::thread::JoinHandle;
+
+    fn companies(rows: &[&[(&str, &str)]]) -> Vec<Record> {
+        rows.iter()
+            .enumerate()
+            .map(|(i, fields)| Record {
+                row: i + 1,
+                fields: fields
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Accepts `requests` BATCH_CREATE calls (every element 201) and returns their bodies.
+    fn segment_api(requests: usize) -> (RestliClient, JoinHandle<Vec<Value>>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", server.server_addr());
+        let handle = std::thread::spawn(move || {
+            let mut bodies = Vec::new();
+            for mut request in server.incoming_requests().take(requests) {
+                let mut raw = String::new();
+                request.as_reader().read_to_string(&mut raw).unwrap();
+                let body: Value = serde_json::from_str(&raw).unwrap();
+                let results: Vec<Value> = body["elements"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|_| json!({ "status": 201 }))
+                    .collect();
+                let response = json!({ "elements": results }).to_string();
+                request
+                    .respond(tiny_http::Response::from_string(response))
+                    .unwrap();
+                bodies.push(body);
+            }
+            bodies
+        });
+        let client = RestliClient::new(
+            base_url,
+            "202601".to_string(),
+            "token".to_string(),
+            "2.0.0".to_string(),
+            Some(5),
+            TunnelMode::Auto,
+        )
+        .unwrap();
+        (client, handle)
+    }
+
+    fn run_sync(
+        client: Option<&RestliClient>,
+        records: &[Record],
+        snapshot_path: &Path,
+        dry_run: bool,
+    ) -> Value {
+        let opts = SyncOptions {
+            segment: "9".to_string(),
+            kind: SegmentKind::Company,
+            batch_size: 1,
+            dry_run,
+        };
+        let mut snapshot = Snapshot::load(snapshot_path, "9", SegmentKind::Company).unwrap();
+        let report_path = snapshot_path.with_extension("report.ndjson");
+        let mut report = ReportWriter::create(&report_path, REPORT_COLUMNS).unwrap();
+        let mapping = ColumnMapping::default();
+        let summary = sync(
+            client,
+            records,
+            &mapping,
+            &opts,
+            &mut snapshot,
+            snapshot_path,
+            &mut report,
+        )
+        .unwrap();
+        report.finish().unwrap();
+        summary
+    }
+
+    #[test]
+    fn company_key_covers_match_fields_only() {
+        let kind = SegmentKind::Company;
+        let acme = json!({ "companyName": "Acme", "city": "Oslo", "industries": ["Tech"] });
+        let moved = json!({ "companyName": "ACME", "city": "Bergen" });
+        let domain = json!({ "companyName": "Acme", "companyWebsiteDomain": "acme.com" });
+        assert_eq!(member_key(kind, &acme), member_key(kind, &moved));
+        assert_ne!(member_key(kind, &acme), member_key(kind, &domain));
+        assert_eq!(
+            snapshot_element(kind, &acme),
+            json!({ "companyName": "Acme" })
+        );
+    }
+
+    #[test]
+    fn sync_removes_companies_matched_by_name_or_domain() {
+        let dir = tempfile::tempdir().unwrap();
+        let snapshot_path = dir.path().join("snapshot.json");
+        let first = companies(&[
+            &[("company_name", "Acme"), ("city", "Oslo")],
+            &[("website", "https://www.foo.com/about")],
+        ]);
+        let (client, api) = segment_api(2);
+        let summary = run_sync(Some(&client), &first, &snapshot_path, false);
+        assert_eq!(summary["add"]["succeeded"], json!(2));
+        assert_eq!(api.join().unwrap().len(), 2);
+        assert!(!journal_path(&snapshot_path).exists());
+
+        // Acme left the list; Foo only changed its city.
+        let second = companies(&[&[("website", "foo.com"), ("city", "Rome")]]);
+        let dry = run_sync(None, &second, &snapshot_path, true);
+        assert_eq!(dry["unchanged"], json!(1));
+        assert_eq!(dry["remove"]["pending"], json!(1));
+
+        let (client, api) = segment_api(1);
+        let summary = run_sync(Some(&client), &second, &snapshot_path, false);
+        assert_eq!(summary["add"]["pending"], json!(0));
+        assert_eq!(summary["remove"]["succeeded"], json!(1));
+        assert_eq!(
+            api.join().unwrap()[0]["elements"],
+            json!([{ "companyName": "Acme", "action": "REMOVE" }])
+        );
+
+        let snapshot = Snapshot::load(&snapshot_path, "9", SegmentKind::Company).unwrap();
+        assert_eq!(
+            snapshot.members.values().collect::<Vec<_>>(),
+            vec![&json!({ "companyWebsiteDomain": "foo.com" })]
+        );
+    }
+
+    #[test]
+    fn load_replays_the_journal_and_ignores_a_torn_tail() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snapshot.json");
+        fs::write(
+            &path,
+            json!({
+                "segment": "urn:li:dmpSegment:9",
+                "type": "company",
+                "members": { "a": { "companyName": "A" }, "b": { "companyName": "B" } },
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let c = json!({ "organizationUrn": "urn:li:organization:3" });
+        let key_c = member_key(SegmentKind::Company, &c);
+        let journal = [
+            json!({ "op": "remove", "key": member_key(SegmentKind::Company, &json!({ "companyName": "A" })) }).to_string(),
+            json!({ "op": "add", "key": key_c, "element": c }).to_string(),
+            r#"{"op":"remove","key":"#.to_string(),
+        ];
+        fs::write(journal_path(&path), journal.join("\n")).unwrap();
+
+        let snapshot = Snapshot::load(&path, "9", SegmentKind::Company).unwrap();
+        let mut names: Vec<&Value> = snapshot.members.values().collect();
+        names.sort_by_key(|v| v.to_string());
+        assert_eq!(names, vec![&json!({ "companyName": "B" }), &c]);
+        assert_eq!(snapshot.members[&key_c], c);
+
+        snapshot.save(&path).unwrap();
+        assert!(!journal_path(&path).exists());
+        let reloaded = Snapshot::load(&path, "9", SegmentKind::Company).unwrap();
+        assert_eq!(reloaded.members, snapshot.members);
+    }
+
+    #[test]
+    fn load_rejects_a_snapshot_for_another_segment() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snapshot.json");
+        Snapshot::load(&path, "9", SegmentKind::User)
+            .unwrap()
+            .save(&path)
+            .unwrap();
+        assert!(Snapshot::load(&path, "10", SegmentKind::User).is_err());
+        assert!(Snapshot::load(&path, "9", SegmentKind::Company).is_err());
+    }
+}
@@ -0,0 +1,201 @@
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::client::RestliClient;
+use crate::restli;
+
+pub const DEFAULT_CAMPAIGN_TYPE: &str = "SPONSORED_UPDATES";
+
+/// Inputs for an `/adBudgetPricing` lookup.
+pub struct PricingQuery {
+    pub account: String,
+    pub bid_type: String,
+    pub campaign_type: String,
+    pub criteria: Value,
+    pub daily_budget: Option<Value>,
+    pub optimization_target: Option<String>,
+}
+
+impl PricingQuery {
+    /// Pricing inputs taken from an existing campaign (`type`, `costType`,
+    /// `targetingCriteria`, `dailyBudget`, `optimizationTargetType`).
+    pub fn from_campaign(account: &str, campaign: &Value) -> Result<Self> {
+        let field = |name: &str| campaign.get(name).and_then(|v| v.as_str());
+        Ok(Self {
+            account: account.to_string(),
+            bid_type: field("costType")
+                .ok_or_else(|| anyhow!("campaign has no costType"))?
+                .to_string(),
+            campaign_type: field("type").unwrap_or(DEFAULT_CAMPAIGN_TYPE).to_string(),
+            criteria: campaign
+                .get("targetingCriteria")
+                .cloned()
+                .ok_or_else(|| anyhow!("campaign has no targetingCriteria"))?,
+            daily_budget: campaign.get("dailyBudget").cloned(),
+            optimization_target: field("optimizationTargetType").map(str::to_string),
+        })
+    }
+
+    /// Pricing inputs for a campaign update: the fields the update's `patch.$set` is
+    /// about to change (new targeting, cost type, budget) win over the stored campaign.
+    pub fn from_update(account: &str, campaign: &Value, update: Option<&Value>) -> Result<Self> {
+        let mut merged = campaign.clone();
+        let pending = update
+            .and_then(|body| body.pointer("/patch/$set"))
+            .and_then(|set| set.as_object());
+        if let (Some(fields), Some(target)) = (pending, merged.as_object_mut()) {
+            for (name, value) in fields {
+                target.insert(name.clone(), value.clone());
+            }
+        }
+        Self::from_campaign(account, &merged)
+    }
+}
+
+/// Suggested bid, bid limits and daily budget limits for `query` (the first pricing
+/// element, with `bidType` added).
+pub fn budget_pricing(client: &RestliClient, query: &PricingQuery) -> Result<Value> {
+    let mut raw = vec![
+        "q=criteriaV2".to_string(),
+        "matchType=EXACT".to_string(),
+        format!(
+            "account={}",
+            restli::escape(&restli::ensure_urn(
+                &query.account,
+                "urn:li:sponsoredAccount"
+            ))
+        ),
+        format!("bidType={}", restli::escape(&query.bid_type)),
+        format!("campaignType={}", restli::escape(&query.campaign_type)),
+        format!(
+            "targetingCriteria={}",
+            restli::encode_value(&query.criteria)
+        ),
+    ];
+    if let Some(budget) = &query.daily_budget {
+        raw.push(format!("dailyBudget={}", restli::encode_value(budget)));
+    }
+    if let Some(target) = &query.optimization_target {
+        raw.push(format!("optimizationTargetType={}", restli::escape(target)));
+    }
+    let path = format!("/adBudgetPricing?{}", raw.join("&"));
+    let resp = client.call("GET", &path, &BTreeMap::new(), &BTreeMap::new(), None)?;
+    let mut pricing = resp
+        .body
+        .get("elements")
+        .and_then(|v| v.as_array())
+        .and_then(|v| v.first())
+        .cloned()
+        .ok_or_else(|| anyhow!("no budget pricing returned for this targeting"))?;
+    pricing["bidType"] = Value::String(query.bid_type.clone());
+    Ok(pricing)
+}
+
+/// `--clamp`: what to do with a bid outside the suggested range.
+#[derive(Debug, Clone, Copy)]
+pub enum Clamp {
+    Reject,
+    Adjust,
+}
+
+impl Clamp {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "reject" => Ok(Self::Reject),
+            "adjust" => Ok(Self::Adjust),
+            other => Err(anyhow!("unknown clamp mode {other:?} (reject|adjust)")),
+        }
+    }
+}
+
+/// Check `amount` against `suggestedBid.min`/`max` of `pricing`. Returns the amount to
+/// send: unchanged when in range, the nearest bound with `Adjust`, an error with `Reject`.
+pub fn clamp_bid(amount: &str, pricing: &Value, clamp: Clamp) -> Result<String> {
+    let bid: f64 = amount
+        .parse()
+        .map_err(|_| anyhow!("invalid bid amount {amount:?}"))?;
+    let bound = |name: &str| -> Option<(f64, String)> {
+        let raw = pricing.pointer(&format!("/suggestedBid/{name}/amount"))?;
+        let text = raw
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| raw.to_string());
+        Some((text.parse().ok()?, text))
+    };
+    let (min, max) = (bound("min"), bound("max"));
+    let out_of_range = match (&min, &max) {
+        (Some((min, text)), _) if bid < *min => Some(("below the suggested minimum", text)),
+        (_, Some((max, text))) if bid > *max => Some(("above the suggested maximum", text)),
+        _ => None,
+    };
+    match (out_of_range, clamp) {
+        (None, _) => Ok(amount.to_string()),
+        (Some((why, bound)), Clamp::Reject) => Err(anyhow!("bid {amount} is {why} {bound}")),
+        (Some((why, bound)), Clamp::Adjust) => {
+            log::warn!("bid {amount} is {why}; adjusting to {bound}");
+            Ok(bound.clone())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn pricing() -> Value {
+        json!({ "suggestedBid": {
+            "min": { "amount": "5.00", "currencyCode": "USD" },
+            "max": { "amount": "12.50", "currencyCode": "USD" },
+        } })
+    }
+
+    #[test]
+    fn in_range_bid_is_unchanged() {
+        assert_eq!(clamp_bid("6.5", &pricing(), Clamp::Reject).unwrap(), "6.5");
+        assert_eq!(
+            clamp_bid("5.00", &pricing(), Clamp::Adjust).unwrap(),
+            "5.00"
+        );
+    }
+
+    #[test]
+    fn out_of_range_bid_is_rejected_or_adjusted() {
+        let err = clamp_bid("4", &pricing(), Clamp::Reject).unwrap_err();
+        assert_eq!(err.to_string(), "bid 4 is below the suggested minimum 5.00");
+        assert_eq!(clamp_bid("4", &pricing(), Clamp::Adjust).unwrap(), "5.00");
+        assert_eq!(clamp_bid("20", &pricing(), Clamp::Adjust).unwrap(), "12.50");
+    }
+
+    #[test]
+    fn update_fields_override_the_stored_campaign() {
+        let campaign = json!({
+            "costType": "CPM",
+            "targetingCriteria": { "include": { "and": [] } },
+            "dailyBudget": { "amount": "50", "currencyCode": "USD" },
+        });
+        let new_criteria = json!({ "include": { "and": [{ "or": {
+            "urn:li:adTargetingFacet:locations": ["urn:li:geo:103644278"],
+        } }] } });
+        let update = json!({ "patch": { "$set": {
+            "targetingCriteria": new_criteria,
+            "unitCost": { "amount": "6", "currencyCode": "USD" },
+        } } });
+
+        let query = PricingQuery::from_update("1", &campaign, Some(&update)).unwrap();
+        assert_eq!(query.criteria, new_criteria);
+        assert_eq!(query.bid_type, "CPM");
+        assert_eq!(query.daily_budget, campaign.get("dailyBudget").cloned());
+
+        let stored = PricingQuery::from_update("1", &campaign, None).unwrap();
+        assert_eq!(stored.criteria, campaign["targetingCriteria"]);
+    }
+
+    #[test]
+    fn missing_bounds_and_bad_amounts() {
+        assert_eq!(clamp_bid("99", &json!({}), Clamp::Reject).unwrap(), "99");
+        assert!(clamp_bid("six", &pricing(), Clamp::Adjust).is_err());
+        assert!(Clamp::parse("round").is_err());
+    }
+}
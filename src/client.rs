@@ -31,6 +31,19 @@ pub struct RestliResponse {
     pub body: Value,
 }
 
+/// Per-element outcome of a `BATCH_CREATE`, in request order.
+#[derive(Debug)]
+pub struct BatchElementResult {
+    pub status: u16,
+    pub error: Option<String>,
+}
+
+impl BatchElementResult {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
 pub struct RestliClient {
     client: Client,
     pub base_url: String,
@@ -92,10 +105,18 @@ impl RestliClient {
         let should_tunnel = self.should_tunnel(&method, &url, &query_pairs)?;
         let mut req = if should_tunnel {
             // Query tunneling: POST + X-HTTP-Method-Override + x-www-form-urlencoded body.
+            // Any query already on the path (Rest.li-encoded) moves into the body verbatim.
+            let mut target = reqwest::Url::parse(&url).context("parse url")?;
+            let mut form = reqwest::Url::parse("http://form.invalid/").context("parse url")?;
+            form.set_query(target.query());
+            form.query_pairs_mut().extend_pairs(&query_pairs);
+            let form_body = form.query().unwrap_or("").to_string();
+            target.set_query(None);
             self.client
-                .post(&url)
+                .post(target.as_str())
                 .header("X-HTTP-Method-Override", method.clone())
-                .form(&query_pairs)
+                .header("Content-Type", "application/x-www-form-urlencoded")
+                .body(form_body)
         } else {
             match method.as_str() {
                 "GET" => self.client.get(&url).query(&query_pairs),
@@ -150,7 +171,7 @@ impl RestliClient {
         let body = if text.trim().is_empty() {
             Value::Null
         } else {
-            serde_json::from_str(&text).unwrap_or_else(|_| Value::String(text))
+            serde_json::from_str(&text).unwrap_or(Value::String(text))
         };
 
         if !status.is_success() {
@@ -164,6 +185,40 @@ impl RestliClient {
         })
     }
 
+    /// `BATCH_CREATE` `elements` under `path`. Elements without an explicit status in
+    /// the response inherit the HTTP status of the call.
+    pub fn batch_create(
+        &self,
+        path: &str,
+        elements: Vec<Value>,
+    ) -> Result<Vec<BatchElementResult>> {
+        let count = elements.len();
+        let body = serde_json::json!({ "elements": elements });
+        let mut headers = BTreeMap::new();
+        headers.insert("X-RestLi-Method".to_string(), "BATCH_CREATE".to_string());
+        let resp = self.call("POST", path, &BTreeMap::new(), &headers, Some(&body))?;
+
+        let results = resp.body.get("elements").and_then(|v| v.as_array());
+        let mut out = Vec::with_capacity(count);
+        for i in 0..count {
+            let result = results.and_then(|r| r.get(i));
+            let status = result
+                .and_then(|r| r.get("status"))
+                .and_then(|v| v.as_u64())
+                .map(|v| v as u16)
+                .unwrap_or(resp.status);
+            let error = result.and_then(|r| r.get("error")).map(|error| {
+                error
+                    .get("message")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+                    .unwrap_or_else(|| error.to_string())
+            });
+            out.push(BatchElementResult { status, error });
+        }
+        Ok(out)
+    }
+
     pub fn put_bytes(
         &self,
         url: &str,
@@ -203,7 +258,7 @@ impl RestliClient {
         let body = if text.trim().is_empty() {
             Value::Null
         } else {
-            serde_json::from_str(&text).unwrap_or_else(|_| Value::String(text))
+            serde_json::from_str(&text).unwrap_or(Value::String(text))
         };
 
         if !status.is_success() {
@@ -229,15 +284,16 @@ impl RestliClient {
 
         match self.tunnel_mode {
             TunnelMode::Never => return Ok(false),
-            TunnelMode::Always => return Ok(!query_pairs.is_empty()),
+            TunnelMode::Always => {
+                return Ok(!query_pairs.is_empty() || base_url.contains('?'));
+            }
             TunnelMode::Auto => {}
         }
 
-        if query_pairs.is_empty() {
+        let mut url = reqwest::Url::parse(base_url).context("parse url")?;
+        if query_pairs.is_empty() && url.query().is_none() {
             return Ok(false);
         }
-
-        let mut url = reqwest::Url::parse(base_url).context("parse url")?;
         {
             let mut qp = url.query_pairs_mut();
             for (k, v) in query_pairs {
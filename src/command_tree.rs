@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::collections::BTreeMap;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -25,7 +26,94 @@ pub struct Operation {
     pub path: String,
     pub headers: Option<BTreeMap<String, String>>,
     pub query: Option<BTreeMap<String, String>>,
+    /// Default JSON body; string leaves may use path placeholders (`{campaign}`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<serde_json::Value>,
     pub params: Vec<ParamDef>,
+    /// First LinkedIn-Version (YYYYMM) this definition applies to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_version: Option<String>,
+    /// Last LinkedIn-Version (YYYYMM) this definition applies to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_version: Option<String>,
+    /// Canonical `resource op` this op is kept around as a compatibility alias for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias_of: Option<String>,
+}
+
+impl Resource {
+    /// Distinct op names in declaration order (an op may have several versioned definitions).
+    pub fn op_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for op in &self.ops {
+            if !names.contains(&op.name.as_str()) {
+                names.push(op.name.as_str());
+            }
+        }
+        names
+    }
+
+    /// Pick the definition of `name` for `version`; falls back to the last declared
+    /// definition when none covers it (callers check `supports_version` to warn).
+    pub fn resolve_op(&self, name: &str, version: &str) -> Option<&Operation> {
+        let mut last = None;
+        for op in self.ops.iter().filter(|op| op.name == name) {
+            if op.supports_version(version) {
+                return Some(op);
+            }
+            last = Some(op);
+        }
+        last
+    }
+
+    pub fn supports_version(&self, version: &str) -> bool {
+        self.ops.iter().any(|op| op.supports_version(version))
+    }
+}
+
+impl Operation {
+    pub fn supports_version(&self, version: &str) -> bool {
+        if let Some(min) = &self.min_version
+            && compare_versions(version, min) == Ordering::Less
+        {
+            return false;
+        }
+        if let Some(max) = &self.max_version
+            && compare_versions(version, max) == Ordering::Greater
+        {
+            return false;
+        }
+        true
+    }
+
+    pub fn version_range(&self) -> String {
+        match (&self.min_version, &self.max_version) {
+            (None, None) => "all".to_string(),
+            (Some(min), None) => format!("{min}+"),
+            (None, Some(max)) => format!("<={max}"),
+            (Some(min), Some(max)) => format!("{min}..={max}"),
+        }
+    }
+}
+
+/// Compare LinkedIn versions numerically (`202401`, `202401.01`).
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parse = |v: &str| -> Vec<u64> {
+        v.trim()
+            .split('.')
+            .map(|part| part.parse::<u64>().unwrap_or(0))
+            .collect()
+    };
+    let (a, b) = (parse(a), parse(b));
+    for i in 0..a.len().max(b.len()) {
+        let x = a.get(i).copied().unwrap_or(0);
+        let y = b.get(i).copied().unwrap_or(0);
+        match x.cmp(&y) {
+            Ordering::Equal => continue,
+            other => return other,
+        }
+    }
+    Ordering::Equal
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -35,6 +123,15 @@ pub struct ParamDef {
     pub flag: String,
     pub param_type: String,
     pub location: ParamLocation,
+    /// Named default (e.g. `account`) filled in by the CLI when the flag is omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    /// Allowed values (enum-typed flags).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub values: Option<Vec<String>>,
+    /// Flags of the same op that fill an overlapping field and cannot be combined.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conflicts_with: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -44,6 +141,8 @@ pub enum ParamLocation {
     Path,
     Query,
     Body,
+    /// Partial-update field, written under `patch.$set`.
+    Patch,
     Header,
 }
 
@@ -51,3 +150,50 @@ pub fn load_command_tree() -> CommandTree {
     let raw = include_str!("../schemas/command_tree.json");
     serde_json::from_str(raw).expect("invalid command_tree.json")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn resource() -> Resource {
+        serde_json::from_value(json!({
+            "name": "video",
+            "ops": [
+                { "name": "upload", "method": "POST", "path": "/old", "params": [], "max_version": "202209" },
+                { "name": "upload", "method": "POST", "path": "/new", "params": [], "min_version": "202210", "max_version": "202405" },
+                { "name": "get", "method": "GET", "path": "/videos", "params": [] },
+            ],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn compares_versions_numerically() {
+        assert_eq!(compare_versions("202401", "202312"), Ordering::Greater);
+        assert_eq!(compare_versions("202401", "202401.00"), Ordering::Equal);
+        assert_eq!(compare_versions("202401.01", "202401"), Ordering::Greater);
+        assert_eq!(compare_versions(" 202401 ", "202401.1"), Ordering::Less);
+    }
+
+    #[test]
+    fn resolves_the_definition_covering_the_version() {
+        let res = resource();
+        let path = |version: &str| res.resolve_op("upload", version).map(|op| op.path.as_str());
+        assert_eq!(path("202209"), Some("/old"));
+        assert_eq!(path("202210"), Some("/new"));
+        assert_eq!(path("202405"), Some("/new"));
+        assert_eq!(res.resolve_op("get", "202601").unwrap().path, "/videos");
+        assert!(res.resolve_op("delete", "202601").is_none());
+    }
+
+    #[test]
+    fn falls_back_to_the_last_definition_outside_every_range() {
+        let res = resource();
+        let op = res.resolve_op("upload", "202406").unwrap();
+        assert_eq!(op.path, "/new");
+        assert!(!op.supports_version("202406"));
+        assert_eq!(op.version_range(), "202210..=202405");
+        assert!(res.supports_version("202406"));
+    }
+}
@@ -0,0 +1,384 @@
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+use std::collections::HashSet;
+
+use crate::client::RestliClient;
+use crate::hashing::{hash_email, is_sha256_hex};
+use crate::records::{ColumnMapping, Record, ReportWriter};
+use crate::timeutil::parse_millis;
+
+pub const DEFAULT_BATCH_SIZE: usize = 5000;
+pub const CONVERSION_URN: &str = "urn:lla:llaPartnerConversion";
+pub const REPORT_COLUMNS: &[&str] = &["row", "event_id", "status", "http_status", "error"];
+
+/// Conversion event fields that can be mapped to input columns.
+pub const FIELDS: &[&str] = &[
+    "email",
+    "sha256_email",
+    "li_fat_id",
+    "click_id",
+    "event_id",
+    "happened_at",
+    "value",
+    "currency",
+    "first_name",
+    "last_name",
+    "company",
+    "title",
+    "country",
+];
+
+pub struct UploadOptions {
+    pub conversion: String,
+    pub batch_size: usize,
+    pub default_currency: Option<String>,
+    pub dry_run: bool,
+}
+
+/// Build, dedupe and send conversion events in `BATCH_CREATE` chunks. Every input row
+/// gets one report line; the returned summary counts rows by outcome.
+pub fn upload(
+    client: Option<&RestliClient>,
+    records: &[Record],
+    mapping: &ColumnMapping,
+    opts: &UploadOptions,
+    report: &mut ReportWriter,
+) -> Result<Value> {
+    let mut seen_event_ids = HashSet::new();
+    let mut pending: Vec<(&Record, Option<String>, Value)> = Vec::new();
+    let mut skipped = 0u64;
+
+    for record in records {
+        match build_event(record, mapping, opts) {
+            Ok((event_id, event)) => {
+                if let Some(id) = &event_id
+                    && !seen_event_ids.insert(id.clone())
+                {
+                    skipped += 1;
+                    write_row(
+                        report,
+                        record.row,
+                        event_id.as_deref(),
+                        "skipped",
+                        None,
+                        Some("duplicate eventId"),
+                    )?;
+                    continue;
+                }
+                pending.push((record, event_id, event));
+            }
+            Err(reason) => {
+                skipped += 1;
+                let event_id = mapping.get(record, "event_id");
+                write_row(report, record.row, event_id, "skipped", None, Some(&reason))?;
+            }
+        }
+    }
+
+    let mut succeeded = 0u64;
+    let mut failed = 0u64;
+    let mut batches = 0u64;
+    let batch_size = opts.batch_size.max(1);
+
+    for chunk in pending.chunks(batch_size) {
+        if opts.dry_run {
+            for (record, event_id, _) in chunk {
+                write_row(
+                    report,
+                    record.row,
+                    event_id.as_deref(),
+                    "dry-run",
+                    None,
+                    None,
+                )?;
+            }
+            continue;
+        }
+        let client = client.ok_or_else(|| anyhow!("client required unless --dry-run"))?;
+        batches += 1;
+
+        let elements: Vec<Value> = chunk.iter().map(|(_, _, event)| event.clone()).collect();
+        match client.batch_create("/conversionEvents", elements) {
+            Ok(results) => {
+                for ((record, event_id, _), result) in chunk.iter().zip(&results) {
+                    if result.is_success() {
+                        succeeded += 1;
+                    } else {
+                        failed += 1;
+                    }
+                    write_row(
+                        report,
+                        record.row,
+                        event_id.as_deref(),
+                        if result.is_success() { "ok" } else { "failed" },
+                        Some(result.status as u64),
+                        result.error.as_deref(),
+                    )?;
+                }
+            }
+            Err(err) => {
+                log::warn!("conversion batch {batches} failed: {err}");
+                for (record, event_id, _) in chunk {
+                    failed += 1;
+                    write_row(
+                        report,
+                        record.row,
+                        event_id.as_deref(),
+                        "failed",
+                        None,
+                        Some(&err.to_string()),
+                    )?;
+                }
+            }
+        }
+    }
+
+    Ok(serde_json::json!({
+        "rows": records.len(),
+        "events": pending.len(),
+        "batches": batches,
+        "succeeded": succeeded,
+        "failed": failed,
+        "skipped": skipped,
+        "dry_run": opts.dry_run,
+    }))
+}
+
+/// Map one row to a conversion event; `Err` carries the skip reason.
+fn build_event(
+    record: &Record,
+    mapping: &ColumnMapping,
+    opts: &UploadOptions,
+) -> std::result::Result<(Option<String>, Value), String> {
+    let mut user_ids = Vec::new();
+    if let Some(email) = mapping.get(record, "email") {
+        let hashed = hash_email(email).ok_or_else(|| format!("invalid email: {email}"))?;
+        user_ids.push(user_id("SHA256_EMAIL", &hashed));
+    } else if let Some(hashed) = mapping.get(record, "sha256_email") {
+        let hashed = hashed.trim();
+        if !is_sha256_hex(hashed) {
+            return Err(format!(
+                "invalid sha256_email (expected 64 hex chars): {hashed}"
+            ));
+        }
+        user_ids.push(user_id("SHA256_EMAIL", &hashed.to_ascii_lowercase()));
+    }
+    // The first-party cookie and the click id are both li_fat_id values.
+    let mut tracking_ids = Vec::new();
+    for field in ["li_fat_id", "click_id"] {
+        if let Some(value) = mapping.get(record, field)
+            && !tracking_ids.contains(&value)
+        {
+            tracking_ids.push(value);
+            user_ids.push(user_id("LINKEDIN_FIRST_PARTY_ADS_TRACKING_UUID", value));
+        }
+    }
+
+    let first_name = mapping.get(record, "first_name");
+    let last_name = mapping.get(record, "last_name");
+    if user_ids.is_empty() && (first_name.is_none() || last_name.is_none()) {
+        return Err("no user identifier (email, li_fat_id, click_id or first+last name)".into());
+    }
+
+    let happened_at = mapping
+        .get(record, "happened_at")
+        .ok_or_else(|| "missing happened_at".to_string())?;
+    let happened_at = parse_millis(happened_at).map_err(|err| err.to_string())?;
+
+    let mut user = serde_json::json!({ "userIds": user_ids });
+    if let (Some(first_name), Some(last_name)) = (first_name, last_name) {
+        let mut info = serde_json::json!({ "firstName": first_name, "lastName": last_name });
+        if let Some(company) = mapping.get(record, "company") {
+            info["companyName"] = Value::String(company.to_string());
+        }
+        if let Some(title) = mapping.get(record, "title") {
+            info["title"] = Value::String(title.to_string());
+        }
+        if let Some(country) = mapping.get(record, "country") {
+            info["countryCode"] = Value::String(country.to_ascii_uppercase());
+        }
+        user["userInfo"] = info;
+    }
+
+    let mut event = serde_json::json!({
+        "conversion": opts.conversion,
+        "conversionHappenedAt": happened_at,
+        "user": user,
+    });
+
+    if let Some(value) = mapping.get(record, "value") {
+        let amount: f64 = value
+            .parse()
+            .map_err(|_| format!("invalid value: {value}"))?;
+        let currency = mapping
+            .get(record, "currency")
+            .map(str::to_string)
+            .or_else(|| opts.default_currency.clone())
+            .ok_or_else(|| {
+                "value without currency (map a column or pass --currency)".to_string()
+            })?;
+        event["conversionValue"] = serde_json::json!({
+            "currencyCode": currency.to_ascii_uppercase(),
+            "amount": amount.to_string(),
+        });
+    }
+
+    let event_id = mapping.get(record, "event_id").map(str::to_string);
+    if let Some(id) = &event_id {
+        event["eventId"] = Value::String(id.clone());
+    }
+    Ok((event_id, event))
+}
+
+fn user_id(id_type: &str, value: &str) -> Value {
+    serde_json::json!({ "idType": id_type, "idValue": value })
+}
+
+fn write_row(
+    report: &mut ReportWriter,
+    row: usize,
+    event_id: Option<&str>,
+    status: &str,
+    http_status: Option<u64>,
+    error: Option<&str>,
+) -> Result<()> {
+    report.write(&serde_json::json!({
+        "row": row,
+        "event_id": event_id,
+        "status": status,
+        "http_status": http_status,
+        "error": error,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashing::sha256_hex;
+    use serde_json::json;
+
+    fn opts(dry_run: bool) -> UploadOptions {
+        UploadOptions {
+            conversion: format!("{CONVERSION_URN}:1"),
+            batch_size: 2,
+            default_currency: None,
+            dry_run,
+        }
+    }
+
+    fn records(rows: &[&[(&str, &str)]]) -> Vec<Record> {
+        rows.iter()
+            .enumerate()
+            .map(|(i, fields)| Record {
+                row: i + 1,
+                fields: fields
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn emails_are_normalized_before_hashing() {
+        let rows = records(&[&[
+            ("email", "  Ada@Example.COM "),
+            ("happened_at", "1700000000000"),
+        ]]);
+        let (_, event) = build_event(&rows[0], &ColumnMapping::default(), &opts(true)).unwrap();
+        assert_eq!(
+            event["user"]["userIds"],
+            json!([{ "idType": "SHA256_EMAIL", "idValue": sha256_hex("ada@example.com") }])
+        );
+        assert_eq!(event["conversionHappenedAt"], json!(1700000000000u64));
+    }
+
+    #[test]
+    fn prehashed_emails_are_lowercased_and_validated() {
+        let digest = sha256_hex("ada@example.com");
+        let rows = records(&[
+            &[
+                ("sha256_email", &digest.to_ascii_uppercase()),
+                ("happened_at", "1"),
+            ],
+            &[("sha256_email", "abc123"), ("happened_at", "1")],
+        ]);
+        let mapping = ColumnMapping::default();
+        let (_, event) = build_event(&rows[0], &mapping, &opts(true)).unwrap();
+        assert_eq!(event["user"]["userIds"][0]["idValue"], json!(digest));
+        let err = build_event(&rows[1], &mapping, &opts(true)).unwrap_err();
+        assert!(err.starts_with("invalid sha256_email"));
+    }
+
+    #[test]
+    fn rows_without_identifier_or_currency_are_skipped() {
+        let rows = records(&[
+            &[("first_name", "Ada"), ("happened_at", "1")],
+            &[("li_fat_id", "x"), ("happened_at", "1"), ("value", "9.5")],
+        ]);
+        let mapping = ColumnMapping::default();
+        assert!(
+            build_event(&rows[0], &mapping, &opts(true))
+                .unwrap_err()
+                .starts_with("no user identifier")
+        );
+        assert!(
+            build_event(&rows[1], &mapping, &opts(true))
+                .unwrap_err()
+                .starts_with("value without currency")
+        );
+    }
+
+    #[test]
+    fn duplicate_event_ids_are_reported_once_as_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.ndjson");
+        let rows = records(&[
+            &[
+                ("email", "a@example.com"),
+                ("happened_at", "1"),
+                ("event_id", "e1"),
+            ],
+            &[
+                ("email", "b@example.com"),
+                ("happened_at", "2"),
+                ("event_id", "e1"),
+            ],
+            &[
+                ("email", "c@example.com"),
+                ("happened_at", "3"),
+                ("event_id", "e2"),
+            ],
+            &[("email", "nope"), ("happened_at", "4")],
+        ]);
+        let mut report = ReportWriter::create(&path, REPORT_COLUMNS).unwrap();
+        let summary = upload(
+            None,
+            &rows,
+            &ColumnMapping::default(),
+            &opts(true),
+            &mut report,
+        )
+        .unwrap();
+        report.finish().unwrap();
+
+        assert_eq!(summary["events"], json!(2));
+        assert_eq!(summary["skipped"], json!(2));
+        let lines: Vec<Value> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        let status = |row: u64| {
+            lines
+                .iter()
+                .find(|l| l["row"] == json!(row))
+                .map(|l| (l["status"].clone(), l["error"].clone()))
+                .unwrap()
+        };
+        assert_eq!(status(1), (json!("dry-run"), Value::Null));
+        assert_eq!(status(2), (json!("skipped"), json!("duplicate eventId")));
+        assert_eq!(status(3).0, json!("dry-run"));
+        assert_eq!(status(4), (json!("skipped"), json!("invalid email: nope")));
+    }
+}
@@ -0,0 +1,147 @@
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::asset_upload;
+use crate::client::RestliClient;
+use crate::uploads::{FileParam, read_all_bytes};
+
+/// Document ads limits: 100 MB and 300 pages.
+pub const MAX_BYTES: u64 = 100 * 1024 * 1024;
+pub const MAX_PAGES: usize = 300;
+pub const EXTENSIONS: &[&str] = &["pdf", "doc", "docx", "ppt", "pptx"];
+
+/// Check type, size and (for PDFs) page count before uploading. Returns the file's
+/// bytes and its page count when it could be determined.
+pub fn validate(file: &FileParam) -> Result<(Vec<u8>, Option<usize>)> {
+    let extension = file
+        .file_name
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+    if !EXTENSIONS.contains(&extension.as_str()) {
+        return Err(anyhow!(
+            "{}: unsupported document type (expected {})",
+            file.file_name,
+            EXTENSIONS.join(", ")
+        ));
+    }
+
+    let bytes = read_all_bytes(file)?;
+    if bytes.len() as u64 > MAX_BYTES {
+        return Err(anyhow!(
+            "{}: {} bytes exceeds the {} MB limit",
+            file.file_name,
+            bytes.len(),
+            MAX_BYTES / (1024 * 1024)
+        ));
+    }
+    if extension != "pdf" {
+        return Ok((bytes, None));
+    }
+
+    if !bytes.starts_with(b"%PDF-") {
+        return Err(anyhow!(
+            "{}: not a PDF (missing %PDF- header)",
+            file.file_name
+        ));
+    }
+    let pages = pdf_page_count(&bytes);
+    match pages {
+        Some(pages) if pages > MAX_PAGES => Err(anyhow!(
+            "{}: {pages} pages exceeds the {MAX_PAGES} page limit",
+            file.file_name
+        )),
+        Some(_) => Ok((bytes, pages)),
+        None => {
+            log::warn!(
+                "{}: could not count pages (compressed object streams); LinkedIn will enforce the {MAX_PAGES} page limit",
+                file.file_name
+            );
+            Ok((bytes, None))
+        }
+    }
+}
+
+/// Count `/Type /Page` objects (not `/Pages`). `None` when pages live in compressed
+/// object streams and none are visible.
+fn pdf_page_count(bytes: &[u8]) -> Option<usize> {
+    const TYPE: &[u8] = b"/Type";
+    let mut count = 0;
+    let mut i = 0;
+    while let Some(pos) = find(&bytes[i..], TYPE) {
+        let mut rest = &bytes[i + pos + TYPE.len()..];
+        while let Some((c, tail)) = rest.split_first()
+            && c.is_ascii_whitespace()
+        {
+            rest = tail;
+        }
+        if rest.starts_with(b"/Page")
+            && !rest[5..].first().is_some_and(|c| c.is_ascii_alphanumeric())
+        {
+            count += 1;
+        }
+        i += pos + TYPE.len();
+    }
+    (count > 0).then_some(count)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Documents API upload: `/documents?action=initializeUpload`, then PUT the bytes to
+/// the returned `uploadUrl`. Returns the `urn:li:document`.
+pub fn upload(client: &RestliClient, owner: &str, bytes: Vec<u8>) -> Result<String> {
+    let req = serde_json::json!({
+        "initializeUploadRequest": { "owner": owner }
+    });
+    let mut query = BTreeMap::new();
+    query.insert("action".to_string(), "initializeUpload".to_string());
+    let resp = client.call("POST", "/documents", &query, &BTreeMap::new(), Some(&req))?;
+
+    let value = resp
+        .body
+        .get("value")
+        .ok_or_else(|| anyhow!("missing response.value"))?;
+    let upload_url = value
+        .get("uploadUrl")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("missing uploadUrl"))?;
+    let document = value
+        .get("document")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("missing document URN"))?;
+
+    client.put_bytes(upload_url, bytes, &BTreeMap::new(), true)?;
+    Ok(document.to_string())
+}
+
+/// Poll `/documents/{urn}` until AVAILABLE; PROCESSING_FAILED is an error.
+pub fn wait_available(client: &RestliClient, document: &str, timeout: Duration) -> Result<Value> {
+    asset_upload::wait_available(
+        client,
+        &format!("/documents/{}", urlencoding::encode(document)),
+        &format!("document {document}"),
+        timeout,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_page_objects_but_not_the_page_tree() {
+        let pdf = b"%PDF-1.4\n1 0 obj << /Type /Pages /Kids [2 0 R 3 0 R] >> endobj\n\
+            2 0 obj << /Type /Page >> endobj\n3 0 obj <</Type/Page/Parent 1 0 R>> endobj\n";
+        assert_eq!(pdf_page_count(pdf), Some(2));
+    }
+
+    #[test]
+    fn no_page_objects_is_unknown() {
+        assert_eq!(pdf_page_count(b"%PDF-1.5\n<< /Type /ObjStm >>"), None);
+        assert_eq!(pdf_page_count(b""), None);
+    }
+}
@@ -0,0 +1,151 @@
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::client::RestliClient;
+use crate::restli;
+
+pub const DAY_MILLIS: u64 = 24 * 60 * 60 * 1000;
+
+/// One forecast input: targeting plus budget and flight dates.
+pub struct Scenario {
+    pub label: String,
+    pub account: String,
+    pub campaign_type: String,
+    pub objective_type: Option<String>,
+    pub optimization_target: Option<String>,
+    pub criteria: Value,
+    /// `{"amount": "100", "currencyCode": "USD"}`
+    pub daily_budget: Value,
+    pub start: u64,
+    pub end: u64,
+}
+
+impl Scenario {
+    pub fn summary(&self) -> Value {
+        serde_json::json!({
+            "label": self.label,
+            "campaignType": self.campaign_type,
+            "objectiveType": self.objective_type,
+            "dailyBudget": self.daily_budget,
+            "runSchedule": { "start": self.start, "end": self.end },
+        })
+    }
+}
+
+/// `/adSupplyForecasts` for a scenario: the first forecast element (impressions,
+/// reach, clicks and spend estimates, with their budget curves).
+pub fn supply_forecast(client: &RestliClient, scenario: &Scenario) -> Result<Value> {
+    let mut raw = vec![
+        "q=criteriaV2".to_string(),
+        format!(
+            "account={}",
+            restli::escape(&restli::ensure_urn(
+                &scenario.account,
+                "urn:li:sponsoredAccount"
+            ))
+        ),
+        format!("campaignType={}", restli::escape(&scenario.campaign_type)),
+        format!(
+            "targetingCriteria={}",
+            restli::encode_value(&scenario.criteria)
+        ),
+        format!(
+            "dailyBudget={}",
+            restli::encode_value(&scenario.daily_budget)
+        ),
+        format!(
+            "runSchedule={}",
+            restli::encode_value(&serde_json::json!({
+                "start": scenario.start,
+                "end": scenario.end,
+            }))
+        ),
+    ];
+    if let Some(objective) = &scenario.objective_type {
+        raw.push(format!("objectiveType={}", restli::escape(objective)));
+    }
+    if let Some(target) = &scenario.optimization_target {
+        raw.push(format!("optimizationTargetType={}", restli::escape(target)));
+    }
+    let path = format!("/adSupplyForecasts?{}", raw.join("&"));
+    let resp = client.call("GET", &path, &BTreeMap::new(), &BTreeMap::new(), None)?;
+    resp.body
+        .get("elements")
+        .and_then(|v| v.as_array())
+        .and_then(|v| v.first())
+        .cloned()
+        .ok_or_else(|| anyhow!("no forecast returned for {}", scenario.label))
+}
+
+/// Leaf values of a forecast keyed by path (`impressions.max`, `curve[2].reach`), so
+/// scenarios with the same response shape line up row by row.
+pub fn flatten_metrics(value: &Value, prefix: &str, out: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten_metrics(value, &path, out);
+            }
+        }
+        Value::Array(items) => {
+            for (i, value) in items.iter().enumerate() {
+                flatten_metrics(value, &format!("{prefix}[{i}]"), out);
+            }
+        }
+        Value::Null => {}
+        Value::String(s) => out.push((prefix.to_string(), s.clone())),
+        other => out.push((prefix.to_string(), other.to_string())),
+    }
+}
+
+/// Metrics as rows, one column per scenario.
+pub fn render_table(labels: &[String], forecasts: &[Value]) -> String {
+    let mut rows: Vec<String> = Vec::new();
+    let columns: Vec<BTreeMap<String, String>> = forecasts
+        .iter()
+        .map(|forecast| {
+            let mut flat = Vec::new();
+            flatten_metrics(forecast, "", &mut flat);
+            for (path, _) in &flat {
+                if !rows.contains(path) {
+                    rows.push(path.clone());
+                }
+            }
+            flat.into_iter().collect()
+        })
+        .collect();
+
+    let metric_width = rows.iter().map(String::len).max().unwrap_or(0).max(6);
+    let widths: Vec<usize> = labels
+        .iter()
+        .zip(&columns)
+        .map(|(label, column)| {
+            column
+                .values()
+                .map(String::len)
+                .chain([label.len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut out = format!("{:<metric_width$}", "metric");
+    for (label, width) in labels.iter().zip(&widths) {
+        out.push_str(&format!("  {label:>width$}"));
+    }
+    out.push('\n');
+    for row in &rows {
+        out.push_str(&format!("{row:<metric_width$}"));
+        for (column, width) in columns.iter().zip(&widths) {
+            let value = column.get(row).map(String::as_str).unwrap_or("-");
+            out.push_str(&format!("  {value:>width$}"));
+        }
+        out.push('\n');
+    }
+    out
+}
@@ -0,0 +1,364 @@
+use anyhow::{Context, Result};
+use reqwest::blocking::Client;
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::Duration;
+
+use crate::client::RestliClient;
+use crate::hashing::hmac_sha256_hex;
+use crate::leads::{self, ExportOptions, Flattener, Watermark};
+use crate::timeutil::{format_millis, now_millis};
+
+pub const DEFAULT_SIGNATURE_HEADER: &str = "X-Signature-256";
+
+/// Default state location: `<config dir>/state/leads-forward-<owner>[-<form>].json`.
+pub fn default_state_path(owner: &str, form: Option<&str>) -> Option<PathBuf> {
+    let export = leads::default_watermark_path(owner, form)?;
+    let name = export
+        .file_name()?
+        .to_str()?
+        .replacen("leads-", "leads-forward-", 1);
+    Some(export.with_file_name(name))
+}
+
+/// HTTP sink: JSON POST with optional HMAC signature and retries.
+pub struct Sink {
+    client: Client,
+    pub url: String,
+    pub secret: Option<String>,
+    pub signature_header: String,
+    pub retries: u32,
+}
+
+impl Sink {
+    pub fn new(
+        url: String,
+        secret: Option<String>,
+        signature_header: String,
+        retries: u32,
+    ) -> Result<Self> {
+        let client = Client::builder()
+            .user_agent("linkedin-ads-cli/0.1.0")
+            .timeout(Duration::from_secs(30))
+            .build()
+            .context("build http client")?;
+        Ok(Self {
+            client,
+            url,
+            secret,
+            signature_header,
+            retries,
+        })
+    }
+
+    /// POST `payload`; retries network errors, 429 and 5xx with exponential backoff.
+    /// Returns the last HTTP status (if any) and error on failure.
+    pub fn send(&self, payload: &Value) -> std::result::Result<u16, (Option<u16>, String)> {
+        let body = serde_json::to_vec(payload).map_err(|err| (None, err.to_string()))?;
+        let mut attempt = 0;
+        loop {
+            let mut req = self
+                .client
+                .post(&self.url)
+                .header("Content-Type", "application/json")
+                .body(body.clone());
+            if let Some(secret) = &self.secret {
+                let signature = hmac_sha256_hex(secret.as_bytes(), &body);
+                req = req.header(&self.signature_header, format!("sha256={signature}"));
+            }
+            let (status, error, retryable) = match req.send() {
+                Ok(resp) if resp.status().is_success() => return Ok(resp.status().as_u16()),
+                Ok(resp) => {
+                    let status = resp.status();
+                    let text = resp.text().unwrap_or_default();
+                    let retryable = status.is_server_error() || status.as_u16() == 429;
+                    (
+                        Some(status.as_u16()),
+                        format!("http {status}: {text}"),
+                        retryable,
+                    )
+                }
+                Err(err) => (None, err.to_string(), true),
+            };
+            if !retryable || attempt >= self.retries {
+                return Err((status, error));
+            }
+            attempt += 1;
+            log::warn!("forward attempt {attempt} failed: {error}; retrying");
+            sleep(Duration::from_secs(1 << attempt.min(6)));
+        }
+    }
+}
+
+/// `--template`: a JSON value whose strings may reference flattened lead fields as
+/// `{field}`. A string that is exactly one placeholder keeps the field's JSON type.
+pub fn render_template(template: &Value, row: &Value) -> Value {
+    match template {
+        Value::String(s) => {
+            if let Some(field) = s.strip_prefix('{').and_then(|v| v.strip_suffix('}'))
+                && !field.contains(['{', '}'])
+            {
+                return row.get(field).cloned().unwrap_or(Value::Null);
+            }
+            let mut out = String::new();
+            let mut rest = s.as_str();
+            while let Some(start) = rest.find('{') {
+                let Some(len) = rest[start..].find('}') else {
+                    break;
+                };
+                out.push_str(&rest[..start]);
+                match row.get(&rest[start + 1..start + len]) {
+                    None | Some(Value::Null) => {}
+                    Some(Value::String(v)) => out.push_str(v),
+                    Some(other) => out.push_str(&other.to_string()),
+                }
+                rest = &rest[start + len + 1..];
+            }
+            out.push_str(rest);
+            Value::String(out)
+        }
+        Value::Array(items) => {
+            Value::Array(items.iter().map(|v| render_template(v, row)).collect())
+        }
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), render_template(v, row)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+pub struct ForwardOptions {
+    pub owner: String,
+    pub form: Option<String>,
+    pub lead_type: String,
+    pub since: Option<u64>,
+    pub template: Option<Value>,
+    pub dead_letter: PathBuf,
+}
+
+/// One poll: fetch responses since the watermark (minus the lookback), forward unseen
+/// ones and checkpoint the state after each lead. Returns per-poll counts.
+pub fn poll_once(
+    client: &RestliClient,
+    flattener: &mut Flattener,
+    sink: &Sink,
+    opts: &ForwardOptions,
+    state: &mut Watermark,
+    state_path: &Path,
+) -> Result<Value> {
+    let now = now_millis();
+    // Without state, start at --since (default: now) so only new leads are sent.
+    let start = match state.start() {
+        Some(start) => start,
+        None => {
+            let start = opts.since.unwrap_or(now);
+            state.submitted_at = Some(start);
+            start
+        }
+    };
+    let export = ExportOptions {
+        owner: opts.owner.clone(),
+        form: opts.form.clone(),
+        lead_type: opts.lead_type.clone(),
+        start,
+        end: now,
+    };
+    let responses = leads::fetch_responses(client, &export)?;
+
+    let (mut forwarded, mut dead_lettered, mut duplicates) = (0u64, 0u64, 0u64);
+    for response in &responses {
+        let id = leads::response_id(response)?;
+        if state.seen.contains_key(id) {
+            duplicates += 1;
+            continue;
+        }
+        let row = flattener.flatten(response);
+        let payload = match &opts.template {
+            Some(template) => render_template(template, &row),
+            None => row,
+        };
+        match sink.send(&payload) {
+            Ok(_) => forwarded += 1,
+            Err((status, error)) => {
+                dead_lettered += 1;
+                log::warn!("lead {id} dead-lettered: {error}");
+                write_dead_letter(&opts.dead_letter, id, status, &error, &payload)?;
+            }
+        }
+        state.record(id, leads::submitted_at(response));
+        state.save(state_path)?;
+    }
+    state.prune();
+    state.save(state_path)?;
+
+    Ok(serde_json::json!({
+        "polled_at": format_millis(export.end),
+        "fetched": responses.len(),
+        "forwarded": forwarded,
+        "dead_lettered": dead_lettered,
+        "duplicates": duplicates,
+        "watermark": state.submitted_at.map(format_millis),
+    }))
+}
+
+fn write_dead_letter(
+    path: &Path,
+    id: &str,
+    status: Option<u16>,
+    error: &str,
+    payload: &Value,
+) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("open {}", path.display()))?;
+    let line = serde_json::json!({
+        "id": id,
+        "failed_at": format_millis(now_millis()),
+        "http_status": status,
+        "error": error,
+        "payload": payload,
+    });
+    writeln!(file, "{line}").with_context(|| format!("write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::TunnelMode;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn whole_placeholder_keeps_the_value_type() {
+        let row = json!({ "score": 7, "email": "a@example.com" });
+        let out = render_template(&json!({ "s": "{score}", "e": ["{email}"] }), &row);
+        assert_eq!(out, json!({ "s": 7, "e": ["a@example.com"] }));
+    }
+
+    #[test]
+    fn embedded_placeholders_are_interpolated() {
+        let row = json!({ "first": "Ada", "last": null, "n": 2 });
+        let out = render_template(&json!("{first} {last}#{n} {missing}"), &row);
+        assert_eq!(out, json!("Ada #2 "));
+    }
+
+    #[test]
+    fn missing_field_and_literals() {
+        let row = json!({});
+        assert_eq!(render_template(&json!("{gone}"), &row), Value::Null);
+        assert_eq!(
+            render_template(&json!("no {brace"), &row),
+            json!("no {brace")
+        );
+        assert_eq!(render_template(&json!(true), &row), json!(true));
+    }
+
+    /// Serves each poll's leads in turn on GET and records the bodies POSTed to `/hook`.
+    fn api(polls: Vec<Value>) -> (String, Arc<Mutex<Vec<Value>>>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", server.server_addr());
+        let forwarded = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&forwarded);
+        std::thread::spawn(move || {
+            let mut polls = polls.into_iter();
+            for mut request in server.incoming_requests() {
+                let body = if request.url().starts_with("/hook") {
+                    let mut raw = String::new();
+                    request.as_reader().read_to_string(&mut raw).unwrap();
+                    sink.lock()
+                        .unwrap()
+                        .push(serde_json::from_str(&raw).unwrap());
+                    json!({})
+                } else {
+                    json!({ "elements": polls.next().unwrap_or(json!([])) })
+                };
+                let response = tiny_http::Response::from_string(body.to_string());
+                request.respond(response).unwrap();
+            }
+        });
+        (base_url, forwarded)
+    }
+
+    #[test]
+    fn late_leads_are_forwarded_once_and_repeats_dropped() {
+        let lead = |id: &str, at: u64| json!({ "id": id, "submittedAt": at });
+        let (base_url, forwarded) = api(vec![
+            json!([lead("r1", 1_000), lead("r2", 2_000)]),
+            // The lookback re-reads r1/r2; r3 arrived late with an older submittedAt.
+            json!([lead("r1", 1_000), lead("r3", 1_500), lead("r2", 2_000)]),
+        ]);
+        let client = RestliClient::new(
+            base_url.clone(),
+            "202601".to_string(),
+            "token".to_string(),
+            "2.0.0".to_string(),
+            Some(5),
+            TunnelMode::Auto,
+        )
+        .unwrap();
+        let sink = Sink::new(
+            format!("{base_url}/hook"),
+            None,
+            DEFAULT_SIGNATURE_HEADER.to_string(),
+            0,
+        )
+        .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let state_path = dir.path().join("state.json");
+        let opts = ForwardOptions {
+            owner: "urn:li:sponsoredAccount:1".to_string(),
+            form: None,
+            lead_type: "SPONSORED".to_string(),
+            since: Some(0),
+            template: Some(json!({ "lead": "{id}" })),
+            dead_letter: dir.path().join("dead.ndjson"),
+        };
+        let mut flattener = Flattener::new(&client);
+        let mut state = Watermark::default();
+
+        let first = poll_once(
+            &client,
+            &mut flattener,
+            &sink,
+            &opts,
+            &mut state,
+            &state_path,
+        );
+        assert_eq!(first.unwrap()["forwarded"], json!(2));
+        let mut state = Watermark::load(&state_path).unwrap();
+        assert_eq!(state.submitted_at, Some(2_000));
+
+        let second = poll_once(
+            &client,
+            &mut flattener,
+            &sink,
+            &opts,
+            &mut state,
+            &state_path,
+        );
+        let second = second.unwrap();
+        assert_eq!(
+            (second["forwarded"].clone(), second["duplicates"].clone()),
+            (json!(1), json!(2))
+        );
+        assert_eq!(
+            *forwarded.lock().unwrap(),
+            [
+                json!({ "lead": "r1" }),
+                json!({ "lead": "r2" }),
+                json!({ "lead": "r3" })
+            ]
+        );
+        assert!(!opts.dead_letter.exists());
+    }
+}
@@ -0,0 +1,46 @@
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+pub fn sha256_hex(value: &str) -> String {
+    hex::encode(Sha256::digest(value.as_bytes()))
+}
+
+/// Trim + lowercase, as LinkedIn expects before hashing.
+pub fn normalize_email(value: &str) -> Option<String> {
+    let email = value.trim().to_ascii_lowercase();
+    if email.is_empty() || !email.contains('@') {
+        return None;
+    }
+    Some(email)
+}
+
+/// SHA-256 of the normalized email. Values that already look like a SHA-256 hex
+/// digest pass through (lowercased) so pre-hashed lists are not double hashed.
+pub fn hash_email(value: &str) -> Option<String> {
+    let trimmed = value.trim();
+    if is_sha256_hex(trimmed) {
+        return Some(trimmed.to_ascii_lowercase());
+    }
+    normalize_email(trimmed).map(|email| sha256_hex(&email))
+}
+
+pub fn is_sha256_hex(value: &str) -> bool {
+    value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Hex HMAC-SHA256 of `body` keyed with `secret`.
+pub fn hmac_sha256_hex(secret: &[u8], body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Constant-time check of a hex HMAC-SHA256 signature.
+pub fn verify_hmac_sha256(secret: &[u8], body: &[u8], signature_hex: &str) -> bool {
+    let Ok(signature) = hex::decode(signature_hex.trim()) else {
+        return false;
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
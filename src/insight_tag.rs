@@ -0,0 +1,108 @@
+use anyhow::{Context, Result, anyhow};
+use reqwest::blocking::Client;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::client::RestliClient;
+use crate::restli;
+
+/// Env override for the origin pages are fetched from by `verify` (e.g. a local server).
+pub const FETCH_BASE_ENV: &str = "LINKEDIN_INSIGHT_FETCH_BASE";
+
+/// The account's Insight Tag (first `/insightTags?q=account` element).
+pub fn account_tag(client: &RestliClient, account: &str) -> Result<Value> {
+    let account = restli::ensure_urn(account, "urn:li:sponsoredAccount");
+    let path = format!(
+        "/insightTags?q=account&account={}",
+        restli::escape(&account)
+    );
+    let resp = client.call("GET", &path, &BTreeMap::new(), &BTreeMap::new(), None)?;
+    resp.body
+        .get("elements")
+        .and_then(|v| v.as_array())
+        .and_then(|v| v.first())
+        .cloned()
+        .ok_or_else(|| anyhow!("no insight tag for {account}"))
+}
+
+/// Partner id of a tag: `partnerId`, else the numeric tail of its id/URN.
+pub fn partner_id(tag: &Value) -> Option<String> {
+    match tag.get("partnerId").or_else(|| tag.get("id"))? {
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) => Some(s.rsplit(':').next().unwrap_or(s).to_string()),
+        _ => None,
+    }
+}
+
+/// The standard Insight Tag snippet for `partner_id`.
+pub fn snippet(partner_id: &str) -> String {
+    format!(
+        r#"<script type="text/javascript">
+_linkedin_partner_id = "{partner_id}";
+window._linkedin_data_partner_ids = window._linkedin_data_partner_ids || [];
+window._linkedin_data_partner_ids.push(_linkedin_partner_id);
+</script><script type="text/javascript">
+(function(l) {{
+if (!l){{window.lintrk = function(a,b){{window.lintrk.q.push([a,b])}};
+window.lintrk.q=[]}}
+var s = document.getElementsByTagName("script")[0];
+var b = document.createElement("script");
+b.type = "text/javascript";b.async = true;
+b.src = "https://snap.licdn.com/li.lms-analytics/insight.min.js";
+s.parentNode.insertBefore(b, s);}})(window.lintrk);
+</script>
+<noscript>
+<img height="1" width="1" style="display:none;" alt="" src="https://px.ads.linkedin.com/collect/?pid={partner_id}&fmt=gif" />
+</noscript>
+"#
+    )
+}
+
+/// `url` with its origin replaced by `base` (path and query kept).
+pub fn rebase_url(url: &str, base: &str) -> String {
+    let rest = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let path = rest.find('/').map(|i| &rest[i..]).unwrap_or("/");
+    format!("{}{path}", base.trim_end_matches('/'))
+}
+
+/// GET a page; returns the final status and body.
+pub fn fetch_page(url: &str) -> Result<(u16, String)> {
+    let client = Client::builder()
+        .user_agent("linkedin-ads-cli/0.1.0")
+        .timeout(Duration::from_secs(30))
+        .build()
+        .context("build http client")?;
+    let resp = client
+        .get(url)
+        .send()
+        .with_context(|| format!("GET {url}"))?;
+    let status = resp.status().as_u16();
+    let body = resp.text().with_context(|| format!("read {url}"))?;
+    Ok((status, body))
+}
+
+/// What a page contains: the Insight script loader and every partner id it sets.
+pub fn inspect_page(html: &str) -> Value {
+    let mut ids: Vec<String> = Vec::new();
+    let markers = [
+        "_linkedin_partner_id",
+        "_linkedin_data_partner_ids.push(",
+        "px.ads.linkedin.com/collect/?pid=",
+        "px.ads.linkedin.com/collect?pid=",
+    ];
+    for marker in markers {
+        for (i, _) in html.match_indices(marker) {
+            let tail = &html[i + marker.len()..];
+            let tail = tail.trim_start_matches([' ', '=', '"', '\'', '(']);
+            let id: String = tail.chars().take_while(|c| c.is_ascii_digit()).collect();
+            if !id.is_empty() && !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+    serde_json::json!({
+        "script_loader": html.contains("snap.licdn.com/li.lms-analytics/insight"),
+        "partner_ids": ids,
+    })
+}
@@ -0,0 +1,652 @@
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::client::RestliClient;
+use crate::profile;
+use crate::restli;
+use crate::timeutil::format_millis;
+
+pub const PAGE_SIZE: usize = 100;
+pub const BASE_COLUMNS: &[&str] = &[
+    "id",
+    "submitted_at",
+    "form",
+    "lead_type",
+    "test_lead",
+    "campaign",
+    "owner",
+];
+
+/// Lead form definition file (YAML). Compiled into a `/leadForms` create body.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LeadFormDefinition {
+    pub name: String,
+    #[serde(default = "default_locale")]
+    pub locale: String,
+    #[serde(default)]
+    pub state: Option<String>,
+    pub headline: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub privacy_policy_url: String,
+    #[serde(default)]
+    pub legal_disclaimer: Option<String>,
+    pub questions: Vec<QuestionDefinition>,
+    #[serde(default)]
+    pub consents: Vec<ConsentDefinition>,
+    #[serde(default)]
+    pub hidden_fields: BTreeMap<String, String>,
+    #[serde(default)]
+    pub thank_you: Option<ThankYouDefinition>,
+}
+
+/// A predefined profile field (`field: EMAIL`) or a custom `text` / `choice` question.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QuestionDefinition {
+    #[serde(default)]
+    pub field: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub question: Option<String>,
+    #[serde(rename = "type", default)]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub options: Vec<String>,
+    #[serde(default)]
+    pub max_length: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConsentDefinition {
+    pub text: String,
+    #[serde(default)]
+    pub required: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThankYouDefinition {
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub landing_page_url: Option<String>,
+}
+
+fn default_locale() -> String {
+    "en_US".to_string()
+}
+
+impl LeadFormDefinition {
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+        serde_norway::from_str(&raw)
+            .with_context(|| format!("invalid lead form {}", path.display()))
+    }
+
+    /// Build the `/leadForms` create body for `owner` (any URN; see [`restli::urn_union`]).
+    pub fn to_body(&self, owner: &str) -> Result<Value> {
+        let text = |value: &str| localized(&self.locale, value);
+        let (language, country) = self
+            .locale
+            .split_once('_')
+            .ok_or_else(|| anyhow!("locale must look like en_US, got {}", self.locale))?;
+
+        let mut names = HashSet::new();
+        let mut questions = Vec::new();
+        for (i, q) in self.questions.iter().enumerate() {
+            let question = compile_question(q, i + 1, &text)?;
+            let name = question["name"].as_str().unwrap_or_default().to_string();
+            if !names.insert(name.clone()) {
+                return Err(anyhow!("duplicate question name {name}"));
+            }
+            questions.push(question);
+        }
+        if questions.is_empty() {
+            return Err(anyhow!("lead form needs at least one question"));
+        }
+
+        let consents: Vec<Value> = self
+            .consents
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                serde_json::json!({
+                    "consentId": i + 1,
+                    "consent": text(&c.text),
+                    "required": c.required,
+                })
+            })
+            .collect();
+        let mut legal_info = serde_json::json!({
+            "privacyPolicyUrl": self.privacy_policy_url,
+            "consents": consents,
+        });
+        if let Some(disclaimer) = &self.legal_disclaimer {
+            legal_info["legalDisclaimer"] = text(disclaimer);
+        }
+
+        let mut content = serde_json::json!({
+            "headline": text(&self.headline),
+            "questions": questions,
+            "legalInfo": legal_info,
+            "hiddenFields": self
+                .hidden_fields
+                .iter()
+                .map(|(name, value)| serde_json::json!({ "name": name, "value": value }))
+                .collect::<Vec<_>>(),
+        });
+        if let Some(description) = &self.description {
+            content["description"] = text(description);
+        }
+        if let Some(thank_you) = &self.thank_you {
+            let mut info = serde_json::Map::new();
+            if let Some(message) = &thank_you.message {
+                info.insert("message".to_string(), text(message));
+            }
+            if let Some(url) = &thank_you.landing_page_url {
+                info.insert("landingPageUrl".to_string(), Value::String(url.clone()));
+                info.insert(
+                    "callToAction".to_string(),
+                    Value::String("VIEW_WEBSITE".to_string()),
+                );
+            }
+            content["postSubmissionInfo"] = Value::Object(info);
+        }
+
+        Ok(serde_json::json!({
+            "owner": restli::urn_union(owner),
+            "name": self.name,
+            "state": self.state.clone().unwrap_or_else(|| "DRAFT".to_string()),
+            "creationLocale": { "language": language, "country": country },
+            "content": content,
+        }))
+    }
+}
+
+fn compile_question(
+    q: &QuestionDefinition,
+    question_id: usize,
+    text: &impl Fn(&str) -> Value,
+) -> Result<Value> {
+    if let Some(field) = &q.field {
+        let field = field.to_ascii_uppercase();
+        let name = q.name.clone().unwrap_or_else(|| field.to_ascii_lowercase());
+        let label = q.question.clone().unwrap_or_else(|| title_case(&field));
+        return Ok(serde_json::json!({
+            "questionId": question_id,
+            "name": name,
+            "predefinedField": field,
+            "question": text(&label),
+            "questionDetails": { "textQuestionDetails": {} },
+        }));
+    }
+
+    let name = q
+        .name
+        .clone()
+        .ok_or_else(|| anyhow!("question {question_id}: custom questions need a name"))?;
+    let label = q
+        .question
+        .clone()
+        .ok_or_else(|| anyhow!("question {name}: missing question text"))?;
+    let details = match q.kind.as_deref().unwrap_or("text") {
+        "text" => {
+            let mut details = serde_json::json!({});
+            if let Some(max) = q.max_length {
+                details["maxResponseLength"] = Value::from(max);
+            }
+            serde_json::json!({ "textQuestionDetails": details })
+        }
+        "choice" => {
+            if q.options.is_empty() {
+                return Err(anyhow!("question {name}: choice questions need options"));
+            }
+            let options: Vec<Value> = q
+                .options
+                .iter()
+                .enumerate()
+                .map(|(i, option)| serde_json::json!({ "optionId": i + 1, "text": text(option) }))
+                .collect();
+            serde_json::json!({ "multipleChoiceQuestionDetails": { "options": options } })
+        }
+        other => {
+            return Err(anyhow!(
+                "question {name}: unknown type {other} (expected: text|choice)"
+            ));
+        }
+    };
+    Ok(serde_json::json!({
+        "questionId": question_id,
+        "name": name,
+        "question": text(&label),
+        "questionDetails": details,
+    }))
+}
+
+fn localized(locale: &str, value: &str) -> Value {
+    serde_json::json!({ "localized": { locale: value } })
+}
+
+/// `FIRST_NAME` -> `First name`.
+fn title_case(field: &str) -> String {
+    let lower = field.replace('_', " ").to_ascii_lowercase();
+    let mut chars = lower.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => lower,
+    }
+}
+
+/// Leads can show up in the API a little after `submittedAt`; each run re-reads this
+/// window before the watermark and relies on the seen-id set to drop repeats.
+pub const LOOKBACK_MILLIS: u64 = 60 * 60 * 1000;
+
+/// Newest `submittedAt` handled for an owner (and optionally one form), plus the
+/// handled response ids with their `submittedAt`. Ids older than the lookback window
+/// are pruned.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Watermark {
+    #[serde(default, alias = "watermark")]
+    pub submitted_at: Option<u64>,
+    #[serde(default)]
+    pub seen: BTreeMap<String, u64>,
+}
+
+impl Watermark {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+        serde_json::from_str(&raw).with_context(|| format!("invalid watermark {}", path.display()))
+    }
+
+    /// Write via a temp file + rename so a crash mid-write keeps the previous state.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec(self)?)
+            .with_context(|| format!("write {}", tmp.display()))?;
+        fs::rename(&tmp, path).with_context(|| format!("write {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Start of the next fetch: the lookback window before the watermark.
+    pub fn start(&self) -> Option<u64> {
+        self.submitted_at
+            .map(|watermark| watermark.saturating_sub(LOOKBACK_MILLIS))
+    }
+
+    pub fn record(&mut self, id: &str, submitted_at: u64) {
+        self.seen.insert(id.to_string(), submitted_at);
+        self.submitted_at = Some(self.submitted_at.unwrap_or(0).max(submitted_at));
+    }
+
+    pub fn prune(&mut self) {
+        if let Some(start) = self.start() {
+            self.seen.retain(|_, submitted_at| *submitted_at >= start);
+        }
+    }
+}
+
+/// Id of a lead response, required to drop repeats across runs.
+pub fn response_id(response: &Value) -> Result<&str> {
+    response
+        .get("id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("lead response without id: {response}"))
+}
+
+/// Default watermark location: `<config dir>/state/leads-<owner id>[-<form id>].json`.
+pub fn default_watermark_path(owner: &str, form: Option<&str>) -> Option<PathBuf> {
+    let mut name = format!("leads-{}", last_id(owner));
+    if let Some(form) = form {
+        name.push('-');
+        name.push_str(&last_id(form));
+    }
+    profile::config_dir().map(|dir| dir.join("state").join(format!("{name}.json")))
+}
+
+/// Trailing id of a URN; for compound keys (`(urn:li:leadGenForm:1,2)`) the inner ids.
+fn last_id(urn: &str) -> String {
+    let ids: Vec<&str> = urn
+        .split([':', ',', '(', ')'])
+        .filter(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+        .collect();
+    if ids.is_empty() {
+        urn.replace([':', '/', '(', ')', ','], "_")
+    } else {
+        ids.join("_")
+    }
+}
+
+pub struct ExportOptions {
+    pub owner: String,
+    pub form: Option<String>,
+    pub lead_type: String,
+    pub start: u64,
+    pub end: u64,
+}
+
+/// All lead responses submitted in `[start, end]`, oldest first.
+pub fn fetch_responses(client: &RestliClient, opts: &ExportOptions) -> Result<Vec<Value>> {
+    let mut path = format!(
+        "/leadFormResponses?q=owner&owner={}&leadType={}&submittedAtTimeRange={}",
+        restli::encode_value(&restli::urn_union(&opts.owner)),
+        restli::encode_value(&serde_json::json!({ "leadType": opts.lead_type })),
+        restli::encode_value(&serde_json::json!({ "start": opts.start, "end": opts.end })),
+    );
+    if let Some(form) = &opts.form {
+        path.push_str("&versionedLeadGenFormUrn=");
+        path.push_str(&restli::escape(form));
+    }
+
+    let mut responses = Vec::new();
+    loop {
+        let page = format!("{path}&start={}&count={PAGE_SIZE}", responses.len());
+        let resp = client.call("GET", &page, &BTreeMap::new(), &BTreeMap::new(), None)?;
+        let elements = resp
+            .body
+            .get("elements")
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+        let fetched = elements.len();
+        responses.extend(elements);
+        let total = resp.body.pointer("/paging/total").and_then(|v| v.as_u64());
+        if fetched < PAGE_SIZE || total.is_some_and(|t| responses.len() as u64 >= t) {
+            break;
+        }
+    }
+    responses.sort_by_key(submitted_at);
+    Ok(responses)
+}
+
+pub fn submitted_at(response: &Value) -> u64 {
+    response
+        .get("submittedAt")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0)
+}
+
+/// Question and consent labels of one lead form.
+#[derive(Debug, Default)]
+struct FormLabels {
+    questions: HashMap<i64, (String, HashMap<i64, String>)>,
+}
+
+/// Flattens responses into rows keyed by question name, fetching each form once.
+pub struct Flattener<'a> {
+    client: &'a RestliClient,
+    forms: HashMap<String, FormLabels>,
+    columns: Vec<String>,
+}
+
+impl<'a> Flattener<'a> {
+    pub fn new(client: &'a RestliClient) -> Self {
+        Self {
+            client,
+            forms: HashMap::new(),
+            columns: BASE_COLUMNS.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    /// Base columns followed by answer columns in order of first appearance.
+    pub fn columns(&self) -> Vec<&str> {
+        self.columns.iter().map(String::as_str).collect()
+    }
+
+    pub fn flatten(&mut self, response: &Value) -> Value {
+        let form = response
+            .get("versionedLeadGenFormUrn")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let mut row = serde_json::json!({
+            "id": response.get("id"),
+            "submitted_at": response.get("submittedAt").and_then(|v| v.as_u64()).map(format_millis),
+            "form": form,
+            "lead_type": response.get("leadType"),
+            "test_lead": response.get("testLead"),
+            "campaign": response.pointer("/leadMetadata/sponsoredLeadMetadata/campaign"),
+            "owner": response
+                .get("owner")
+                .and_then(|v| v.as_object())
+                .and_then(|o| o.values().next()),
+        });
+
+        let labels = self.labels(&form);
+        let mut answers = Vec::new();
+        for answer in response
+            .pointer("/formResponse/answers")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let question_id = answer
+                .get("questionId")
+                .and_then(|v| v.as_i64())
+                .unwrap_or(0);
+            let (name, options) = labels
+                .and_then(|l| l.questions.get(&question_id))
+                .map(|(name, options)| (name.clone(), Some(options)))
+                .unwrap_or_else(|| (format!("question_{question_id}"), None));
+            let details = answer.get("answerDetails");
+            let value = if let Some(text) = details
+                .and_then(|d| d.pointer("/textQuestionAnswer/answer"))
+                .and_then(|v| v.as_str())
+            {
+                text.to_string()
+            } else {
+                details
+                    .and_then(|d| d.pointer("/multipleChoiceAnswer/options"))
+                    .and_then(|v| v.as_array())
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|id| id.as_i64())
+                    .map(|id| {
+                        options
+                            .and_then(|o| o.get(&id))
+                            .cloned()
+                            .unwrap_or_else(|| id.to_string())
+                    })
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            };
+            answers.push((name, Value::String(value)));
+        }
+        for consent in response
+            .pointer("/formResponse/consentResponses")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let id = consent
+                .get("consentId")
+                .and_then(|v| v.as_i64())
+                .unwrap_or(0);
+            let accepted = consent.get("accepted").cloned().unwrap_or(Value::Null);
+            answers.push((format!("consent_{id}"), accepted));
+        }
+
+        for (name, value) in answers {
+            // A question named like a base column must not overwrite e.g. the id.
+            let name = if BASE_COLUMNS.contains(&name.as_str()) {
+                format!("{name}_answer")
+            } else {
+                name
+            };
+            if !self.columns.contains(&name) {
+                self.columns.push(name.clone());
+            }
+            row[name] = value;
+        }
+        row
+    }
+
+    fn labels(&mut self, form: &str) -> Option<&FormLabels> {
+        let form_id = form
+            .split([',', '(', ')'])
+            .find(|part| part.starts_with("urn:li:leadGenForm:"))
+            .and_then(|urn| urn.rsplit(':').next())?
+            .to_string();
+        if !self.forms.contains_key(&form_id) {
+            let labels = match self.client.call(
+                "GET",
+                &format!("/leadForms/{form_id}"),
+                &BTreeMap::new(),
+                &BTreeMap::new(),
+                None,
+            ) {
+                Ok(resp) => form_labels(&resp.body),
+                Err(err) => {
+                    log::warn!("lead form {form_id} lookup failed: {err}; using question ids");
+                    FormLabels::default()
+                }
+            };
+            self.forms.insert(form_id.clone(), labels);
+        }
+        self.forms.get(&form_id)
+    }
+}
+
+fn form_labels(form: &Value) -> FormLabels {
+    let mut labels = FormLabels::default();
+    for question in form
+        .pointer("/content/questions")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+    {
+        let Some(id) = question.get("questionId").and_then(|v| v.as_i64()) else {
+            continue;
+        };
+        let name = question
+            .get("name")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .or_else(|| {
+                question
+                    .get("predefinedField")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_ascii_lowercase)
+            })
+            .unwrap_or_else(|| format!("question_{id}"));
+        let options = question
+            .pointer("/questionDetails/multipleChoiceQuestionDetails/options")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|option| {
+                let id = option.get("optionId")?.as_i64()?;
+                let text = option
+                    .pointer("/text/localized")
+                    .and_then(|v| v.as_object())
+                    .and_then(|m| m.values().next())
+                    .and_then(|v| v.as_str())?;
+                Some((id, text.to_string()))
+            })
+            .collect();
+        labels.questions.insert(id, (name, options));
+    }
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::TunnelMode;
+    use serde_json::json;
+
+    const HOUR: u64 = LOOKBACK_MILLIS;
+
+    #[test]
+    fn watermark_rereads_the_lookback_window_and_prunes_older_ids() {
+        let mut watermark = Watermark::default();
+        assert_eq!(watermark.start(), None);
+
+        watermark.record("a", 10 * HOUR);
+        watermark.record("b", 12 * HOUR);
+        // A late lead with an older submittedAt does not move the watermark back.
+        watermark.record("c", 11 * HOUR + 1);
+        assert_eq!(watermark.submitted_at, Some(12 * HOUR));
+        assert_eq!(watermark.start(), Some(11 * HOUR));
+
+        watermark.prune();
+        let ids: Vec<&str> = watermark.seen.keys().map(String::as_str).collect();
+        assert_eq!(ids, ["b", "c"]);
+    }
+
+    #[test]
+    fn watermark_round_trips_and_reads_the_forwarder_field_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("leads-1.json");
+        assert!(Watermark::load(&path).unwrap().submitted_at.is_none());
+
+        let mut watermark = Watermark::default();
+        watermark.record("a", 5);
+        watermark.save(&path).unwrap();
+        let loaded = Watermark::load(&path).unwrap();
+        assert_eq!(
+            (loaded.submitted_at, loaded.seen),
+            (Some(5), watermark.seen)
+        );
+
+        fs::write(&path, r#"{"watermark": 7, "seen": {}}"#).unwrap();
+        assert_eq!(Watermark::load(&path).unwrap().submitted_at, Some(7));
+    }
+
+    #[test]
+    fn answers_named_like_base_columns_do_not_overwrite_them() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", server.server_addr());
+        let form = json!({ "content": { "questions": [
+            { "questionId": 1, "name": "id" },
+            { "questionId": 2, "name": "company" },
+        ] } });
+        let api = std::thread::spawn(move || {
+            let request = server.incoming_requests().next().unwrap();
+            assert_eq!(request.url(), "/leadForms/7");
+            let response = tiny_http::Response::from_string(form.to_string());
+            request.respond(response).unwrap();
+        });
+        let client = RestliClient::new(
+            base_url,
+            "202601".to_string(),
+            "token".to_string(),
+            "2.0.0".to_string(),
+            Some(5),
+            TunnelMode::Auto,
+        )
+        .unwrap();
+
+        let mut flattener = Flattener::new(&client);
+        let row = flattener.flatten(&json!({
+            "id": "lead-1",
+            "versionedLeadGenFormUrn": "urn:li:versionedLeadGenForm:(urn:li:leadGenForm:7,1)",
+            "formResponse": { "answers": [
+                { "questionId": 1, "answerDetails": { "textQuestionAnswer": { "answer": "X-9" } } },
+                { "questionId": 2, "answerDetails": { "textQuestionAnswer": { "answer": "Acme" } } },
+            ] },
+        }));
+        api.join().unwrap();
+
+        assert_eq!(row["id"], json!("lead-1"));
+        assert_eq!(row["id_answer"], json!("X-9"));
+        assert_eq!(row["company"], json!("Acme"));
+        assert_eq!(
+            flattener.columns()[BASE_COLUMNS.len()..],
+            ["id_answer", "company"]
+        );
+    }
+}
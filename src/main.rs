@@ -1,9 +1,28 @@
+mod account_users;
 mod asset_upload;
+mod audiences;
+mod bidding;
 mod client;
 mod command_tree;
+mod conversions;
+mod documents;
+mod forecast;
+mod forwarding;
+mod hashing;
+mod insight_tag;
+mod leads;
+mod media_checks;
+mod organizations;
 mod params;
+mod previews;
+mod profile;
+mod records;
+mod restli;
 mod s3;
+mod targeting;
+mod timeutil;
 mod uploads;
+mod webhook;
 
 use anyhow::{Context, Result, anyhow};
 use clap::{Arg, ArgAction, Command};
@@ -13,8 +32,12 @@ use serde_json::Value;
 use std::collections::BTreeMap;
 use std::env;
 use std::io::Write;
+use std::path::{Path, PathBuf};
 
-use asset_upload::{DEFAULT_IMAGE_RECIPE, DEFAULT_VIDEO_RECIPE, upload_image, upload_video};
+use asset_upload::{
+    DEFAULT_IMAGE_RECIPE, DEFAULT_VIDEO_RECIPE, VideoUploadOptions, upload_image, upload_image_api,
+    upload_video, upload_video_api,
+};
 use client::{RestliClient, TunnelMode};
 
 fn main() {
@@ -26,14 +49,16 @@ fn main() {
 
 fn run() -> Result<()> {
     let tree = command_tree::load_command_tree();
-    let cli = build_cli(&tree);
+    let version = active_linkedin_version(&tree);
+    let cli = build_cli(&tree, &version);
     let matches = cli.get_matches();
+    init_logger(&matches);
 
     if let Some(matches) = matches.subcommand_matches("list") {
-        return handle_list(&tree, matches);
+        return handle_list(&tree, &version, matches);
     }
     if let Some(matches) = matches.subcommand_matches("describe") {
-        return handle_describe(&tree, matches);
+        return handle_describe(&tree, &version, matches);
     }
     if let Some(matches) = matches.subcommand_matches("tree") {
         return handle_tree(&tree, matches);
@@ -41,32 +66,13 @@ fn run() -> Result<()> {
     if let Some(matches) = matches.subcommand_matches("s3") {
         return handle_s3(matches);
     }
-    if let Some(matches) = matches.subcommand_matches("image") {
-        return handle_image(&tree, matches);
-    }
-    if let Some(matches) = matches.subcommand_matches("video") {
-        return handle_video(&tree, matches);
+    if let Some(matches) = matches.subcommand_matches("forecast") {
+        return handle_forecast(&tree, matches);
     }
     if let Some(matches) = matches.subcommand_matches("raw") {
         return handle_raw(&tree, matches);
     }
 
-    let config = load_config(&tree, &matches)?;
-    let client = RestliClient::new(
-        config.base_url,
-        config.linkedin_version,
-        config.access_token,
-        config.restli_protocol_version,
-        config.timeout,
-        config.tunnel_mode,
-    )?;
-
-    let pretty = matches.get_flag("pretty");
-    let raw_output = matches.get_flag("raw");
-    let all = matches.get_flag("all");
-    let max_pages = matches.get_one::<u64>("max_pages").copied().unwrap_or(0);
-    let max_items = matches.get_one::<u64>("max_items").copied().unwrap_or(0);
-
     let (res_name, res_matches) = matches
         .subcommand()
         .ok_or_else(|| anyhow!("resource required"))?;
@@ -74,20 +80,54 @@ fn run() -> Result<()> {
         .subcommand()
         .ok_or_else(|| anyhow!("operation required"))?;
 
-    let op = find_op(&tree, res_name, op_name)
+    if let Some(result) = run_helper(&tree, res_name, op_name, op_matches) {
+        return result;
+    }
+
+    let client = client_from_matches(&tree, &matches)?;
+
+    let pretty = matches.get_flag("pretty");
+    let raw_output = matches.get_flag("raw");
+    let all = matches.get_flag("all");
+    let max_pages = matches.get_one::<u64>("max_pages").copied().unwrap_or(0);
+    let max_items = matches.get_one::<u64>("max_items").copied().unwrap_or(0);
+
+    let op = find_op(&tree, res_name, op_name, &client.linkedin_version)
         .ok_or_else(|| anyhow!("unknown command {res_name} {op_name}"))?;
+    if !op.supports_version(&client.linkedin_version) {
+        log::warn!(
+            "{res_name} {op_name} is not available in LinkedIn-Version {} (supported: {})",
+            client.linkedin_version,
+            op.version_range()
+        );
+    }
 
-    let id = res_matches
-        .get_one::<String>("resource_id")
-        .cloned()
-        .or_else(|| default_account_id(res_name))
-        .or_else(|| default_asset_id(res_name));
+    let account_id = default_account_id(&matches)?;
+    let mut id = res_matches.get_one::<String>("resource_id").cloned();
+    if id.is_none() && res_name == "ad-account" {
+        id = account_id.clone();
+    }
+    let id = id.or_else(|| default_asset_id(res_name));
+
+    let mut defaults = BTreeMap::new();
+    if let Some(account_id) = account_id {
+        defaults.insert("account".to_string(), account_id);
+    }
 
     let params_json = op_matches.get_one::<String>("params");
     let fields = op_matches.get_one::<String>("fields");
     let select = op_matches.get_one::<String>("select");
 
-    let built = build_request(op, id.as_deref(), op_matches, params_json, fields, select)?;
+    let mut built = build_request(
+        op,
+        id.as_deref(),
+        &defaults,
+        op_matches,
+        params_json,
+        fields,
+        select,
+    )?;
+    resolve_caller_params(&tree, &client, op, op_matches, &mut built)?;
     let response = if all {
         paginate_all(
             &client,
@@ -123,6 +163,19 @@ fn run() -> Result<()> {
     Ok(())
 }
 
+/// Set up logging before dispatch so commands that never build a client (`webhook
+/// serve`, local file checks) still print warnings.
+fn init_logger(matches: &clap::ArgMatches) {
+    let level = if matches.get_flag("debug") {
+        log::LevelFilter::Debug
+    } else {
+        log::LevelFilter::Warn
+    };
+    env_logger::Builder::from_env("RUST_LOG")
+        .filter_level(level)
+        .init();
+}
+
 fn load_config(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<Config> {
     let access_token = matches
         .get_one::<String>("access_token")
@@ -156,16 +209,6 @@ fn load_config(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<Config>
         .transpose()?
         .unwrap_or(client::TunnelMode::Auto);
 
-    if matches.get_flag("debug") {
-        env_logger::Builder::from_env("RUST_LOG")
-            .filter_level(log::LevelFilter::Debug)
-            .init();
-    } else {
-        env_logger::Builder::from_env("RUST_LOG")
-            .filter_level(log::LevelFilter::Warn)
-            .init();
-    }
-
     Ok(Config {
         access_token,
         linkedin_version,
@@ -176,7 +219,19 @@ fn load_config(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<Config>
     })
 }
 
-fn build_cli(tree: &CommandTree) -> Command {
+fn client_from_matches(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<RestliClient> {
+    let config = load_config(tree, matches)?;
+    RestliClient::new(
+        config.base_url,
+        config.linkedin_version,
+        config.access_token,
+        config.restli_protocol_version,
+        config.timeout,
+        config.tunnel_mode,
+    )
+}
+
+fn build_cli(tree: &CommandTree, version: &str) -> Command {
     let mut cmd = Command::new("linkedin-ads")
         .about("LinkedIn Marketing API CLI (Rest.li /rest)")
         .subcommand_required(true)
@@ -202,6 +257,13 @@ fn build_cli(tree: &CommandTree) -> Command {
                 .value_name("URL")
                 .help("API base URL (env: LINKEDIN_BASE_URL)"),
         )
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .global(true)
+                .value_name("NAME")
+                .help("Config profile for defaults such as the ad account (env: LINKEDIN_PROFILE)"),
+        )
         .arg(
             Arg::new("restli_protocol_version")
                 .long("restli-protocol-version")
@@ -367,81 +429,109 @@ fn build_cli(tree: &CommandTree) -> Command {
     );
 
     cmd = cmd.subcommand(
-        Command::new("image")
-            .about("Image helpers (Assets API)")
-            .subcommand_required(true)
-            .arg_required_else_help(true)
-            .subcommand(
-                Command::new("upload")
-                    .arg(
-                        Arg::new("owner")
-                            .long("owner")
-                            .value_name("URN")
-                            .required(true),
-                    )
-                    .arg(
-                        Arg::new("file")
-                            .long("file")
-                            .value_name("FILE|URL|S3")
-                            .required(true),
-                    )
-                    .arg(
-                        Arg::new("recipe")
-                            .long("recipe")
-                            .value_name("URN")
-                            .default_value(DEFAULT_IMAGE_RECIPE),
-                    ),
-            ),
-    );
-
-    cmd = cmd.subcommand(
-        Command::new("video")
-            .about("Video helpers (Assets API)")
-            .subcommand_required(true)
-            .arg_required_else_help(true)
-            .subcommand(
-                Command::new("upload")
-                    .arg(
-                        Arg::new("owner")
-                            .long("owner")
-                            .value_name("URN")
-                            .required(true),
-                    )
-                    .arg(
-                        Arg::new("file")
-                            .long("file")
-                            .value_name("FILE|URL|S3")
-                            .required(true),
-                    )
-                    .arg(
-                        Arg::new("recipe")
-                            .long("recipe")
-                            .value_name("URN")
-                            .default_value(DEFAULT_VIDEO_RECIPE),
-                    )
-                    .arg(
-                        Arg::new("wait")
-                            .long("wait")
-                            .action(ArgAction::SetTrue)
-                            .help("Wait for processing (polls /assets/{id})"),
-                    ),
+        Command::new("forecast")
+            .about("Impressions/reach forecasts for one or more scenarios, side by side")
+            .arg(
+                Arg::new("targeting")
+                    .long("targeting")
+                    .value_name("FILE")
+                    .action(ArgAction::Append)
+                    .help("Targeting YAML (repeatable; one scenario per file)"),
+            )
+            .arg(
+                Arg::new("draft")
+                    .long("draft")
+                    .value_name("FILE")
+                    .action(ArgAction::Append)
+                    .help("Campaign draft JSON/YAML with targetingCriteria (repeatable)"),
+            )
+            .arg(
+                Arg::new("campaign")
+                    .long("campaign")
+                    .value_name("ID")
+                    .action(ArgAction::Append)
+                    .help("Existing campaign to forecast (repeatable)"),
+            )
+            .arg(
+                Arg::new("daily_budget")
+                    .long("daily-budget")
+                    .value_name("AMOUNT[,AMOUNT...]")
+                    .value_delimiter(',')
+                    .help("Daily budget(s); each is a separate scenario (default: the draft's)"),
+            )
+            .arg(
+                Arg::new("currency")
+                    .long("currency")
+                    .value_name("CODE")
+                    .help("Budget currency (default: the draft's, else the ad account's)"),
+            )
+            .arg(
+                Arg::new("days")
+                    .long("days")
+                    .value_name("N")
+                    .value_parser(clap::value_parser!(u64))
+                    .default_value("30")
+                    .help("Flight duration in days"),
+            )
+            .arg(
+                Arg::new("start")
+                    .long("start")
+                    .value_name("DATE")
+                    .help("Flight start (default: now)"),
+            )
+            .arg(
+                Arg::new("campaign_type")
+                    .long("campaign-type")
+                    .value_name("TYPE")
+                    .value_parser([
+                        "TEXT_AD",
+                        "SPONSORED_UPDATES",
+                        "SPONSORED_INMAILS",
+                        "DYNAMIC",
+                    ])
+                    .help("Campaign type (default: the draft's, else SPONSORED_UPDATES)"),
+            )
+            .arg(
+                Arg::new("objective")
+                    .long("objective")
+                    .value_name("TYPE")
+                    .help("Objective type, e.g. BRAND_AWARENESS, WEBSITE_VISIT"),
+            )
+            .arg(account_arg())
+            .arg(
+                Arg::new("json")
+                    .long("json")
+                    .action(ArgAction::SetTrue)
+                    .help("Print JSON (scenario + forecast per entry) instead of a table"),
             ),
     );
 
     for resource in &tree.resources {
         let mut res_cmd = Command::new(resource.name.clone())
             .about(resource.name.clone())
+            .hide(!resource.supports_version(version))
             .subcommand_required(true)
             .arg_required_else_help(true)
             .arg(
                 Arg::new("resource_id")
                     .long("id")
+                    .global(true)
                     .value_name("ID")
                     .help("Primary resource id (env: LINKEDIN_AD_ACCOUNT_ID for ad-account)"),
             );
 
-        for op in &resource.ops {
-            let mut op_cmd = Command::new(op.name.clone()).about(op.path.clone());
+        for op_name in resource.op_names() {
+            let Some(op) = resource.resolve_op(op_name, version) else {
+                continue;
+            };
+            // Ops outside the active version stay callable (with a warning) but are hidden.
+            let about = match &op.alias_of {
+                Some(alias_of) => format!("{} (alias of `{alias_of}`)", op.path),
+                None => op.path.clone(),
+            };
+            let mut op_cmd = Command::new(op.name.clone())
+                .about(about)
+                .hide(!op.supports_version(version));
             op_cmd = op_cmd.arg(
                 Arg::new("params")
                     .long("params")
@@ -469,76 +559,896 @@ fn build_cli(tree: &CommandTree) -> Command {
         cmd = cmd.subcommand(res_cmd);
     }
 
-    cmd
-}
+    cmd = attach_helper(
+        cmd,
+        "conversions",
+        "Conversions API helpers",
+        Command::new("upload")
+            .about("Upload offline conversion events (/conversionEvents) from CSV or NDJSON")
+            .arg(
+                Arg::new("conversion")
+                    .long("conversion")
+                    .value_name("URN|ID")
+                    .required(true)
+                    .help("Conversion rule (urn:lla:llaPartnerConversion:...)"),
+            )
+            .arg(
+                Arg::new("file")
+                    .long("file")
+                    .value_name("FILE|URL|S3")
+                    .required(true),
+            )
+            .arg(
+                Arg::new("format")
+                    .long("format")
+                    .value_name("FORMAT")
+                    .value_parser(["csv", "ndjson"])
+                    .help("Input format (default: from file extension)"),
+            )
+            .arg(
+                Arg::new("mapping")
+                    .long("mapping")
+                    .value_name("JSON|@FILE")
+                    .help(format!(
+                        "Field -> column mapping ({})",
+                        conversions::FIELDS.join(", ")
+                    )),
+            )
+            .arg(
+                Arg::new("currency")
+                    .long("currency")
+                    .value_name("CODE")
+                    .help("Currency for rows with a value but no currency column"),
+            )
+            .arg(
+                Arg::new("batch_size")
+                    .long("batch-size")
+                    .value_name("N")
+                    .value_parser(clap::value_parser!(usize))
+                    .default_value(conversions::DEFAULT_BATCH_SIZE.to_string()),
+            )
+            .arg(
+                Arg::new("report")
+                    .long("report")
+                    .value_name("FILE")
+                    .help("Per-row result report, .csv or .ndjson (default: <input>.report.csv)"),
+            )
+            .arg(
+                Arg::new("dry_run")
+                    .long("dry-run")
+                    .action(ArgAction::SetTrue)
+                    .help("Validate and report without sending"),
+            ),
+    );
 
-fn build_param_arg(param: &command_tree::ParamDef) -> Arg {
-    let mut arg = Arg::new(param_key(param))
-        .long(param.flag.clone())
-        .value_name(param.param_type.clone());
-    if param.param_type.starts_with("list<") {
-        arg = arg.action(ArgAction::Append);
-    }
-    arg
-}
+    cmd = attach_helper(
+        cmd,
+        "audience",
+        "Matched audience (DMP segment) helpers",
+        segment_list_args(
+            Command::new("upload")
+                .about("Upload a contact or company list to a segment from CSV or NDJSON"),
+        )
+        .arg(
+            Arg::new("action")
+                .long("action")
+                .value_name("ACTION")
+                .value_parser(["ADD", "REMOVE"])
+                .default_value("ADD"),
+        ),
+    );
+    cmd = attach_helper(
+        cmd,
+        "audience",
+        "Matched audience (DMP segment) helpers",
+        segment_list_args(
+            Command::new("sync")
+                .about("Send only the adds/removes since the last sync of this list to a segment"),
+        )
+        .arg(
+            Arg::new("snapshot")
+                .long("snapshot")
+                .value_name("FILE")
+                .help("Snapshot of members last sent (default: <config dir>/snapshots/dmpSegment-<id>-<type>.json)"),
+        ),
+    );
+    cmd = attach_helper(
+        cmd,
+        "audience",
+        "Matched audience (DMP segment) helpers",
+        Command::new("status")
+            .about("Show segment build status and match rate")
+            .arg(segment_arg())
+            .arg(
+                Arg::new("wait")
+                    .long("wait")
+                    .action(ArgAction::SetTrue)
+                    .help("Poll until the segment is READY"),
+            )
+            .arg(
+                Arg::new("interval")
+                    .long("interval")
+                    .value_name("SECONDS")
+                    .value_parser(clap::value_parser!(u64))
+                    .default_value("30"),
+            )
+            .arg(
+                Arg::new("wait_timeout")
+                    .long("wait-timeout")
+                    .value_name("SECONDS")
+                    .value_parser(clap::value_parser!(u64))
+                    .default_value("3600")
+                    .help("Give up waiting after this long (the global --timeout is per HTTP request)"),
+            ),
+    );
 
-fn handle_list(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
-    if matches.get_flag("json") {
-        let mut out = Vec::new();
-        for res in &tree.resources {
-            let ops: Vec<String> = res.ops.iter().map(|op| op.name.clone()).collect();
-            out.push(serde_json::json!({"resource": res.name, "ops": ops}));
-        }
-        write_json(&Value::Array(out), true)?;
-        return Ok(());
-    }
+    cmd = attach_helper(
+        cmd,
+        "lead-form",
+        "Lead Gen forms",
+        Command::new("create")
+            .about("Create a lead form from a YAML definition")
+            .arg(
+                Arg::new("file")
+                    .long("file")
+                    .value_name("YAML")
+                    .required(true)
+                    .help(
+                        "Form definition: name, headline, questions, consents, hidden_fields, ...",
+                    ),
+            )
+            .arg(owner_arg())
+            .arg(
+                Arg::new("dry_run")
+                    .long("dry-run")
+                    .action(ArgAction::SetTrue)
+                    .help("Print the request body without sending"),
+            ),
+    );
+    cmd = attach_helper(
+        cmd,
+        "leads",
+        "Lead Gen responses",
+        lead_query_args(
+            Command::new("export")
+                .about("Export new lead responses since the last export to CSV or NDJSON"),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .value_name("FILE")
+                .required(true)
+                .help("Output file, .csv or .ndjson"),
+        )
+        .arg(
+            Arg::new("since")
+                .long("since")
+                .value_name("TIMESTAMP")
+                .help("Start of the time range, overriding the stored watermark"),
+        )
+        .arg(
+            Arg::new("state")
+                .long("state")
+                .value_name("FILE")
+                .help("Watermark file (default: <config dir>/state/leads-<owner>[-<form>].json)"),
+        ),
+    );
+    cmd = attach_helper(
+        cmd,
+        "leads",
+        "Lead Gen responses",
+        lead_query_args(
+            Command::new("forward")
+                .about("Poll for new lead responses and POST each one as JSON to a webhook"),
+        )
+        .arg(
+            Arg::new("to")
+                .long("to")
+                .value_name("URL")
+                .required(true)
+                .help("Target webhook URL"),
+        )
+        .arg(
+            Arg::new("interval")
+                .long("interval")
+                .value_name("SECONDS")
+                .value_parser(clap::value_parser!(u64))
+                .default_value("60"),
+        )
+        .arg(
+            Arg::new("once")
+                .long("once")
+                .action(ArgAction::SetTrue)
+                .help("Poll once and exit"),
+        )
+        .arg(
+            Arg::new("secret")
+                .long("secret")
+                .value_name("SECRET")
+                .help("HMAC-SHA256 signing secret (env: LEADS_FORWARD_SECRET)"),
+        )
+        .arg(
+            Arg::new("signature_header")
+                .long("signature-header")
+                .value_name("HEADER")
+                .default_value(forwarding::DEFAULT_SIGNATURE_HEADER),
+        )
+        .arg(
+            Arg::new("retries")
+                .long("retries")
+                .value_name("N")
+                .value_parser(clap::value_parser!(u32))
+                .default_value("3"),
+        )
+        .arg(
+            Arg::new("template")
+                .long("template")
+                .value_name("JSON|@FILE")
+                .help("Payload template; strings may use {question_name} placeholders"),
+        )
+        .arg(
+            Arg::new("dead_letter")
+                .long("dead-letter")
+                .value_name("FILE")
+                .help("NDJSON file for leads that could not be delivered (default: next to the state file)"),
+        )
+        .arg(
+            Arg::new("since")
+                .long("since")
+                .value_name("TIMESTAMP")
+                .help("Where to start when there is no state yet (default: now)"),
+        )
+        .arg(
+            Arg::new("state")
+                .long("state")
+                .value_name("FILE")
+                .help("State file (default: <config dir>/state/leads-forward-<owner>[-<form>].json)"),
+        ),
+    );
 
-    for res in &tree.resources {
-        write_stdout_line(&res.name)?;
-        for op in &res.ops {
-            write_stdout_line(&format!("  {}", op.name))?;
-        }
-    }
-    Ok(())
-}
+    cmd = attach_helper(
+        cmd,
+        "webhook",
+        "LinkedIn webhook receiver",
+        Command::new("serve")
+            .about("Receive lead/event notifications: answer challenges, verify signatures, emit NDJSON")
+            .arg(
+                Arg::new("port")
+                    .long("port")
+                    .value_name("PORT")
+                    .value_parser(clap::value_parser!(u16))
+                    .default_value("8080"),
+            )
+            .arg(
+                Arg::new("bind")
+                    .long("bind")
+                    .value_name("ADDR")
+                    .default_value("127.0.0.1"),
+            )
+            .arg(
+                Arg::new("secret")
+                    .long("secret")
+                    .value_name("SECRET")
+                    .help("App client secret used for challenges and X-LI-Signature (env: LINKEDIN_CLIENT_SECRET)"),
+            )
+            .arg(
+                Arg::new("path")
+                    .long("path")
+                    .value_name("PATH")
+                    .help("Only accept requests on this path (default: any)"),
+            )
+            .arg(
+                Arg::new("enrich")
+                    .long("enrich")
+                    .action(ArgAction::SetTrue)
+                    .help("Fetch the full lead response for lead notifications"),
+            )
+            .arg(
+                Arg::new("exec")
+                    .long("exec")
+                    .value_name("COMMAND")
+                    .conflicts_with("file")
+                    .help("Run a shell command per notification with the JSON on stdin"),
+            )
+            .arg(
+                Arg::new("file")
+                    .long("file")
+                    .value_name("FILE")
+                    .help("Append notifications to an NDJSON file instead of stdout"),
+            ),
+    );
 
-fn handle_describe(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
-    let resource = matches
-        .get_one::<String>("resource")
-        .ok_or_else(|| anyhow!("resource required"))?;
-    let op_name = matches
-        .get_one::<String>("op")
-        .ok_or_else(|| anyhow!("operation required"))?;
+    cmd = attach_helper(
+        cmd,
+        "targeting",
+        "Targeting facets and entities",
+        Command::new("search")
+            .about("Look up targeting entity URNs by name (typeahead)")
+            .arg(Arg::new("query").value_name("TEXT").required(true))
+            .arg(
+                Arg::new("facet")
+                    .long("facet")
+                    .value_name("FACET")
+                    .required(true)
+                    .help(
+                        "Facet name or URN: locations, titles, industries, skills, employers, ...",
+                    ),
+            )
+            .arg(
+                Arg::new("limit")
+                    .long("limit")
+                    .value_name("N")
+                    .value_parser(clap::value_parser!(usize))
+                    .default_value("20"),
+            )
+            .arg(
+                Arg::new("json")
+                    .long("json")
+                    .action(ArgAction::SetTrue)
+                    .help("Print JSON instead of URN/name lines"),
+            ),
+    );
 
-    let op = find_op(tree, resource, op_name)
-        .ok_or_else(|| anyhow!("unknown command {resource} {op_name}"))?;
+    cmd = attach_helper(
+        cmd,
+        "campaign",
+        "Campaigns",
+        Command::new("explain-targeting")
+            .about("Show a campaign's targeting with entity names and the audience count")
+            .arg(account_arg())
+            .arg(
+                Arg::new("json")
+                    .long("json")
+                    .action(ArgAction::SetTrue)
+                    .help("Print JSON (criteria, names, audience count) instead of a tree"),
+            ),
+    );
 
-    if matches.get_flag("json") {
-        write_json(&serde_json::to_value(op)?, true)?;
-        return Ok(());
-    }
+    cmd = attach_helper(
+        cmd,
+        "accounts",
+        "Ad accounts you can access",
+        Command::new("list")
+            .about("Ad accounts of the authenticated member, with name, currency, status and role")
+            .arg(
+                Arg::new("json")
+                    .long("json")
+                    .action(ArgAction::SetTrue)
+                    .help("Print JSON instead of a table"),
+            ),
+    );
+    cmd = attach_helper(
+        cmd,
+        "accounts",
+        "Ad accounts you can access",
+        Command::new("use")
+            .about("Save the default ad account in the active profile")
+            .arg(
+                Arg::new("account")
+                    .value_name("ID|NAME")
+                    .required(true)
+                    .help("Account id/URN, or an exact account name"),
+            ),
+    );
 
-    write_stdout_line(&format!("{} {}", resource, op.name))?;
-    write_stdout_line(&format!("  method: {}", op.method))?;
-    write_stdout_line(&format!("  path: {}", op.path))?;
-    if let Some(query) = &op.query {
-        write_stdout_line("  query defaults:")?;
-        for (k, v) in query {
-            write_stdout_line(&format!("    {}={}", k, v))?;
-        }
-    }
-    if let Some(headers) = &op.headers {
+    cmd = attach_helper(
+        cmd,
+        "creative",
+        "Creatives",
+        Command::new("preview")
+            .about("Save ad previews as HTML, with a gallery page (index.html)")
+            .arg(
+                Arg::new("creative")
+                    .long("creative")
+                    .value_name("URN|ID")
+                    .action(ArgAction::Append)
+                    .help("Creative to preview (repeatable)"),
+            )
+            .arg(
+                Arg::new("campaign")
+                    .long("campaign")
+                    .value_name("ID")
+                    .help("Preview every creative of this campaign"),
+            )
+            .arg(
+                Arg::new("placement")
+                    .long("placement")
+                    .value_name("PLACEMENT")
+                    .help("Only this placement (default: every placement LinkedIn returns)"),
+            )
+            .arg(
+                Arg::new("out")
+                    .long("out")
+                    .value_name("DIR")
+                    .default_value("previews")
+                    .help("Output directory"),
+            )
+            .arg(account_arg()),
+    );
+
+    cmd = attach_helper(
+        cmd,
+        "image",
+        "Images",
+        Command::new("upload")
+            .about("Upload an image (Images API; Assets API with --legacy-assets)")
+            .arg(
+                Arg::new("owner")
+                    .long("owner")
+                    .value_name("URN|ID|VANITY|auto")
+                    .required(true)
+                    .help(OWNER_HELP),
+            )
+            .arg(
+                Arg::new("file")
+                    .long("file")
+                    .value_name("FILE|URL|S3")
+                    .required(true),
+            )
+            .arg(
+                Arg::new("legacy_assets")
+                    .long("legacy-assets")
+                    .action(ArgAction::SetTrue)
+                    .help("Use /assets?action=registerUpload (returns a digitalmediaAsset URN)"),
+            )
+            .arg(
+                Arg::new("recipe")
+                    .long("recipe")
+                    .value_name("URN")
+                    .default_value(DEFAULT_IMAGE_RECIPE)
+                    .help("Assets API recipe (legacy uploads only)"),
+            ),
+    );
+
+    cmd = attach_helper(
+        cmd,
+        "video",
+        "Videos",
+        Command::new("upload")
+            .about("Upload a video (Videos API; Assets API with --legacy-assets)")
+            .arg(
+                Arg::new("owner")
+                    .long("owner")
+                    .value_name("URN|ID|VANITY|auto")
+                    .required(true)
+                    .help(OWNER_HELP),
+            )
+            .arg(
+                Arg::new("file")
+                    .long("file")
+                    .value_name("FILE|URL|S3")
+                    .required(true),
+            )
+            .arg(
+                Arg::new("wait")
+                    .long("wait")
+                    .action(ArgAction::SetTrue)
+                    .help("Wait for processing (polls /videos/{urn}, or /assets/{id} when legacy)"),
+            )
+            .arg(
+                Arg::new("captions")
+                    .long("captions")
+                    .value_name("FILE|URL|S3")
+                    .help("SRT captions to attach"),
+            )
+            .arg(
+                Arg::new("thumbnail")
+                    .long("thumbnail")
+                    .value_name("FILE|URL|S3")
+                    .help("Custom thumbnail (JPG or PNG, up to 2 MB)"),
+            )
+            .arg(
+                Arg::new("legacy_assets")
+                    .long("legacy-assets")
+                    .action(ArgAction::SetTrue)
+                    .help("Use /assets?action=registerUpload (returns a digitalmediaAsset URN)"),
+            )
+            .arg(
+                Arg::new("recipe")
+                    .long("recipe")
+                    .value_name("URN")
+                    .default_value(DEFAULT_VIDEO_RECIPE)
+                    .help("Assets API recipe (legacy uploads only)"),
+            ),
+    );
+
+    cmd = attach_helper(
+        cmd,
+        "document",
+        "Documents",
+        Command::new("upload")
+            .about("Upload a document (PDF, DOC(X), PPT(X)) and wait until it can be posted")
+            .arg(
+                Arg::new("owner")
+                    .long("owner")
+                    .value_name("URN|ID|VANITY|auto")
+                    .required(true)
+                    .help(OWNER_HELP),
+            )
+            .arg(
+                Arg::new("file")
+                    .long("file")
+                    .value_name("FILE|URL|S3")
+                    .required(true),
+            ),
+    );
+
+    cmd = attach_helper(
+        cmd,
+        "insight-tag",
+        "Insight Tag",
+        Command::new("snippet")
+            .about("Print the Insight Tag JavaScript snippet for the account")
+            .arg(account_arg())
+            .arg(partner_id_arg()),
+    );
+    cmd = attach_helper(
+        cmd,
+        "insight-tag",
+        "Insight Tag",
+        Command::new("verify")
+            .about("Fetch a page and check that it loads the Insight Tag with the account's partner id")
+            .arg(
+                Arg::new("url")
+                    .long("url")
+                    .value_name("URL")
+                    .required(true),
+            )
+            .arg(
+                Arg::new("fetch_base")
+                    .long("fetch-base")
+                    .value_name("URL")
+                    .help(format!(
+                        "Fetch from this origin instead of the URL's, e.g. a local server (env: {})",
+                        insight_tag::FETCH_BASE_ENV
+                    )),
+            )
+            .arg(account_arg())
+            .arg(partner_id_arg()),
+    );
+
+    cmd = attach_helper(
+        cmd,
+        "users",
+        "Ad account users and roles",
+        Command::new("grant")
+            .about("Give a member a role on several ad accounts")
+            .arg(
+                Arg::new("user")
+                    .long("user")
+                    .value_name("URN|ID")
+                    .required(true)
+                    .help("Member (urn:li:person:...)"),
+            )
+            .arg(
+                Arg::new("role")
+                    .long("role")
+                    .value_name("ROLE")
+                    .required(true)
+                    .value_parser(clap::builder::PossibleValuesParser::new(
+                        account_users::ROLES,
+                    )),
+            )
+            .arg(
+                Arg::new("accounts")
+                    .long("accounts")
+                    .value_name("all|ID[,ID...]")
+                    .required(true)
+                    .value_delimiter(',')
+                    .help("Account ids, or `all` for every account you can manage users on"),
+            )
+            .arg(
+                Arg::new("dry_run")
+                    .long("dry-run")
+                    .action(ArgAction::SetTrue)
+                    .help("Show the planned add/update per account without changing anything"),
+            ),
+    );
+
+    cmd = attach_helper(
+        cmd,
+        "bid",
+        "Bid and budget suggestions",
+        Command::new("suggest")
+            .about("Suggested, minimum and maximum bid and daily budget limits (adBudgetPricing)")
+            .arg(
+                Arg::new("campaign")
+                    .long("campaign")
+                    .value_name("ID")
+                    .conflicts_with("targeting")
+                    .help("Use an existing campaign's targeting, cost type and campaign type"),
+            )
+            .arg(
+                Arg::new("targeting")
+                    .long("targeting")
+                    .value_name("FILE")
+                    .help("Targeting YAML (see `campaign create --targeting`)"),
+            )
+            .arg(
+                Arg::new("cost_type")
+                    .long("cost-type")
+                    .value_name("TYPE")
+                    .value_parser(["CPM", "CPC", "CPV"])
+                    .help("Bid type (required with --targeting; overrides the campaign's)"),
+            )
+            .arg(
+                Arg::new("campaign_type")
+                    .long("campaign-type")
+                    .value_name("TYPE")
+                    .value_parser([
+                        "TEXT_AD",
+                        "SPONSORED_UPDATES",
+                        "SPONSORED_INMAILS",
+                        "DYNAMIC",
+                    ])
+                    .help("Campaign type (default: the campaign's, else SPONSORED_UPDATES)"),
+            )
+            .arg(account_arg()),
+    );
+
+    cmd
+}
+
+/// Owner / form / lead type args shared by `leads export` and `leads forward`.
+fn lead_query_args(cmd: Command) -> Command {
+    cmd.arg(owner_arg())
+        .arg(
+            Arg::new("form")
+                .long("form")
+                .value_name("URN")
+                .help("Only this form (urn:li:versionedLeadGenForm:...)"),
+        )
+        .arg(
+            Arg::new("lead_type")
+                .long("lead-type")
+                .value_name("TYPE")
+                .value_parser(["SPONSORED", "EVENT", "COMPANY", "ORGANIZATION_PRODUCT"])
+                .default_value("SPONSORED"),
+        )
+}
+
+fn owner_arg() -> Arg {
+    Arg::new("owner")
+        .long("owner")
+        .value_name("URN|ID")
+        .help("Owner: ad account id/URN or urn:li:organization:... (default: active ad account)")
+}
+
+/// Input args shared by `audience upload` and `audience sync`.
+fn segment_list_args(cmd: Command) -> Command {
+    cmd.arg(segment_arg())
+        .arg(
+            Arg::new("file")
+                .long("file")
+                .value_name("FILE|URL|S3")
+                .required(true),
+        )
+        .arg(
+            Arg::new("type")
+                .long("type")
+                .value_name("TYPE")
+                .value_parser(["user", "company"])
+                .default_value("user")
+                .help("List type: user (hashed emails) or company"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .value_parser(["csv", "ndjson"])
+                .help("Input format (default: from file extension)"),
+        )
+        .arg(
+            Arg::new("mapping")
+                .long("mapping")
+                .value_name("JSON|@FILE")
+                .help(format!(
+                    "Field -> column mapping (user: {}; company: {})",
+                    audiences::USER_FIELDS.join(", "),
+                    audiences::COMPANY_FIELDS.join(", ")
+                )),
+        )
+        .arg(
+            Arg::new("batch_size")
+                .long("batch-size")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .default_value(audiences::DEFAULT_BATCH_SIZE.to_string()),
+        )
+        .arg(
+            Arg::new("report")
+                .long("report")
+                .value_name("FILE")
+                .help("Per-row result report, .csv or .ndjson (default: <input>.report.csv)"),
+        )
+        .arg(
+            Arg::new("dry_run")
+                .long("dry-run")
+                .action(ArgAction::SetTrue)
+                .help("Normalize, hash and report without sending"),
+        )
+}
+
+fn account_arg() -> Arg {
+    Arg::new("account")
+        .long("account")
+        .value_name("ID")
+        .help("Ad account id (default: active ad account)")
+}
+
+const OWNER_HELP: &str =
+    "Organization URN, id or vanity name, or `auto` for the only organization you can post as";
+
+fn partner_id_arg() -> Arg {
+    Arg::new("partner_id")
+        .long("partner-id")
+        .value_name("ID")
+        .help("Insight Tag partner id (default: looked up for the account)")
+}
+
+fn segment_arg() -> Arg {
+    Arg::new("segment")
+        .long("segment")
+        .value_name("URN|ID")
+        .required(true)
+        .help("DMP segment (urn:li:dmpSegment:...)")
+}
+
+/// Attach a hand-written op under `resource`, creating the resource command when the
+/// command tree does not define it.
+fn attach_helper(cmd: Command, resource: &str, about: &str, op: Command) -> Command {
+    if cmd.find_subcommand(resource).is_some() {
+        return cmd.mut_subcommand(resource, |res| res.subcommand(op));
+    }
+    cmd.subcommand(
+        Command::new(resource.to_string())
+            .about(about.to_string())
+            .subcommand_required(true)
+            .arg_required_else_help(true)
+            .subcommand(op),
+    )
+}
+
+/// Dispatch hand-written ops; `None` falls through to the command tree.
+fn run_helper(
+    tree: &CommandTree,
+    resource: &str,
+    op: &str,
+    matches: &clap::ArgMatches,
+) -> Option<Result<()>> {
+    let result = match (resource, op) {
+        ("conversions", "upload") => handle_conversions_upload(tree, matches),
+        ("audience", "upload") => handle_audience_upload(tree, matches),
+        ("audience", "sync") => handle_audience_sync(tree, matches),
+        ("audience", "status") => handle_audience_status(tree, matches),
+        ("lead-form", "create") => handle_lead_form_create(tree, matches),
+        ("leads", "export") => handle_leads_export(tree, matches),
+        ("leads", "forward") => handle_leads_forward(tree, matches),
+        ("webhook", "serve") => handle_webhook_serve(tree, matches),
+        ("targeting", "search") => handle_targeting_search(tree, matches),
+        ("campaign", "explain-targeting") => handle_campaign_explain_targeting(tree, matches),
+        ("bid", "suggest") => handle_bid_suggest(tree, matches),
+        ("users", "grant") => handle_users_grant(tree, matches),
+        ("accounts", "list") => handle_accounts_list(tree, matches),
+        ("accounts", "use") => handle_accounts_use(tree, matches),
+        ("creative", "preview") => handle_creative_preview(tree, matches),
+        ("image", "upload") => handle_image_upload(tree, matches),
+        ("video", "upload") => handle_video_upload(tree, matches),
+        ("document", "upload") => handle_document_upload(tree, matches),
+        ("insight-tag", "snippet") => handle_insight_tag_snippet(tree, matches),
+        ("insight-tag", "verify") => handle_insight_tag_verify(tree, matches),
+        _ => return None,
+    };
+    Some(result)
+}
+
+fn build_param_arg(param: &command_tree::ParamDef) -> Arg {
+    let mut arg = Arg::new(param_key(param))
+        .long(param.flag.clone())
+        .value_name(param.param_type.clone());
+    if param.param_type.starts_with("list<") {
+        arg = arg.action(ArgAction::Append);
+    }
+    if let Some(values) = &param.values {
+        arg = arg.value_parser(clap::builder::PossibleValuesParser::new(values.clone()));
+    }
+    if let Some(flags) = &param.conflicts_with {
+        arg = arg.conflicts_with_all(flags.iter().map(|flag| params::flag_key(flag)));
+    }
+    arg
+}
+
+fn handle_list(tree: &CommandTree, version: &str, matches: &clap::ArgMatches) -> Result<()> {
+    let available = |res: &command_tree::Resource| -> Vec<String> {
+        res.op_names()
+            .into_iter()
+            .filter(|name| {
+                res.resolve_op(name, version)
+                    .map(|op| op.supports_version(version))
+                    .unwrap_or(false)
+            })
+            .map(|name| name.to_string())
+            .collect()
+    };
+
+    if matches.get_flag("json") {
+        let mut out = Vec::new();
+        for res in &tree.resources {
+            let ops = available(res);
+            if ops.is_empty() {
+                continue;
+            }
+            out.push(serde_json::json!({"resource": res.name, "ops": ops}));
+        }
+        write_json(&Value::Array(out), true)?;
+        return Ok(());
+    }
+
+    for res in &tree.resources {
+        let ops = available(res);
+        if ops.is_empty() {
+            continue;
+        }
+        write_stdout_line(&res.name)?;
+        for op in ops {
+            write_stdout_line(&format!("  {}", op))?;
+        }
+    }
+    Ok(())
+}
+
+fn handle_describe(tree: &CommandTree, version: &str, matches: &clap::ArgMatches) -> Result<()> {
+    let resource = matches
+        .get_one::<String>("resource")
+        .ok_or_else(|| anyhow!("resource required"))?;
+    let op_name = matches
+        .get_one::<String>("op")
+        .ok_or_else(|| anyhow!("operation required"))?;
+
+    let op = find_op(tree, resource, op_name, version)
+        .ok_or_else(|| anyhow!("unknown command {resource} {op_name}"))?;
+
+    if matches.get_flag("json") {
+        write_json(&serde_json::to_value(op)?, true)?;
+        return Ok(());
+    }
+
+    write_stdout_line(&format!("{} {}", resource, op.name))?;
+    write_stdout_line(&format!("  method: {}", op.method))?;
+    write_stdout_line(&format!("  path: {}", op.path))?;
+    write_stdout_line(&format!("  versions: {}", op.version_range()))?;
+    if let Some(alias_of) = &op.alias_of {
+        write_stdout_line(&format!("  alias of: {alias_of}"))?;
+    }
+    if !op.supports_version(version) {
+        write_stdout_line(&format!("  (not available in LinkedIn-Version {version})"))?;
+    }
+    if let Some(query) = &op.query {
+        write_stdout_line("  query defaults:")?;
+        for (k, v) in query {
+            write_stdout_line(&format!("    {}={}", k, v))?;
+        }
+    }
+    if let Some(headers) = &op.headers {
         write_stdout_line("  headers defaults:")?;
         for (k, v) in headers {
             write_stdout_line(&format!("    {}: {}", k, v))?;
         }
     }
+    if let Some(body) = &op.body {
+        write_stdout_line(&format!("  body defaults: {body}"))?;
+    }
     if !op.params.is_empty() {
         write_stdout_line("  params:")?;
         for param in &op.params {
+            let default = param
+                .default
+                .as_ref()
+                .map(|source| format!("  [default: {source}]"))
+                .unwrap_or_default();
             write_stdout_line(&format!(
-                "    --{}  {}  ({:?})",
-                param.flag, param.param_type, param.location
+                "    --{}  {}  ({:?}){}",
+                param.flag, param.param_type, param.location, default
             ))?;
         }
     }
@@ -554,6 +1464,146 @@ fn handle_tree(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
     Ok(())
 }
 
+fn handle_forecast(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let account = helper_account(matches)?;
+    let client = client_from_matches(tree, matches)?;
+    let values = |name: &str| -> Vec<String> {
+        matches
+            .get_many::<String>(name)
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect()
+    };
+
+    // (label, campaign-like JSON with at least targetingCriteria)
+    let mut sources: Vec<(String, Value)> = Vec::new();
+    for campaign in values("campaign") {
+        let id = campaign.rsplit(':').next().unwrap_or(&campaign).to_string();
+        sources.push((
+            format!("campaign {id}"),
+            fetch_campaign(&client, &account, &id)?,
+        ));
+    }
+    for file in values("draft") {
+        let raw = std::fs::read_to_string(&file).with_context(|| format!("read {file}"))?;
+        let draft: Value =
+            serde_norway::from_str(&raw).with_context(|| format!("invalid draft {file}"))?;
+        if draft.get("targetingCriteria").is_none() {
+            return Err(anyhow!("{file} has no targetingCriteria"));
+        }
+        sources.push((file_label(&file), draft));
+    }
+    if !values("targeting").is_empty() {
+        let mut resolver = targeting::Resolver::new(&client);
+        for file in values("targeting") {
+            let criteria = targeting::compile_file(Path::new(&file), &mut resolver)?;
+            sources.push((
+                file_label(&file),
+                serde_json::json!({ "targetingCriteria": criteria }),
+            ));
+        }
+    }
+    if sources.is_empty() {
+        return Err(anyhow!("--targeting, --draft or --campaign required"));
+    }
+
+    let start = match matches.get_one::<String>("start") {
+        Some(start) => timeutil::parse_millis(start)?,
+        None => timeutil::now_millis(),
+    };
+    let days = matches.get_one::<u64>("days").copied().unwrap_or(30);
+    let budgets = values("daily_budget");
+    let mut account_currency: Option<String> = None;
+    let mut scenarios = Vec::new();
+    for (label, source) in &sources {
+        let field = |name: &str| {
+            source
+                .get(name)
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+        };
+        let currency = match matches.get_one::<String>("currency").cloned().or_else(|| {
+            Some(
+                source
+                    .pointer("/dailyBudget/currencyCode")?
+                    .as_str()?
+                    .to_string(),
+            )
+        }) {
+            Some(currency) => currency,
+            None => match &account_currency {
+                Some(currency) => currency.clone(),
+                None => {
+                    let currency = account_users::account_currency(&client, &account)
+                        .context("no budget currency; pass --currency")?;
+                    account_currency.insert(currency).clone()
+                }
+            },
+        };
+        let amounts = if budgets.is_empty() {
+            let amount = source
+                .pointer("/dailyBudget/amount")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow!("--daily-budget required for {label}"))?;
+            vec![amount.to_string()]
+        } else {
+            budgets.clone()
+        };
+        for amount in &amounts {
+            scenarios.push(forecast::Scenario {
+                label: if amounts.len() > 1 {
+                    format!("{label} @ {amount}")
+                } else {
+                    label.clone()
+                },
+                account: account.clone(),
+                campaign_type: matches
+                    .get_one::<String>("campaign_type")
+                    .cloned()
+                    .or_else(|| field("type"))
+                    .unwrap_or_else(|| bidding::DEFAULT_CAMPAIGN_TYPE.to_string()),
+                objective_type: matches
+                    .get_one::<String>("objective")
+                    .cloned()
+                    .or_else(|| field("objectiveType")),
+                optimization_target: field("optimizationTargetType"),
+                criteria: source["targetingCriteria"].clone(),
+                daily_budget: serde_json::json!({ "amount": amount, "currencyCode": currency }),
+                start,
+                end: start + days * forecast::DAY_MILLIS,
+            });
+        }
+    }
+
+    let mut forecasts = Vec::new();
+    for scenario in &scenarios {
+        forecasts.push(forecast::supply_forecast(&client, scenario)?);
+    }
+
+    if matches.get_flag("json") {
+        let out: Vec<Value> = scenarios
+            .iter()
+            .zip(&forecasts)
+            .map(|(scenario, forecast)| {
+                serde_json::json!({ "scenario": scenario.summary(), "forecast": forecast })
+            })
+            .collect();
+        return write_json(&Value::Array(out), matches.get_flag("pretty"));
+    }
+    let labels: Vec<String> = scenarios.iter().map(|s| s.label.clone()).collect();
+    write_stdout_line(forecast::render_table(&labels, &forecasts).trim_end_matches('\n'))
+}
+
+/// Scenario label for a file: its stem (`eu-engineers.yaml` -> `eu-engineers`).
+fn file_label(file: &str) -> String {
+    Path::new(file)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(file)
+        .to_string()
+}
+
 fn handle_raw(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
     let method = matches
         .get_one::<String>("method")
@@ -584,15 +1634,7 @@ fn handle_raw(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
         None
     };
 
-    let config = load_config(tree, matches)?;
-    let client = RestliClient::new(
-        config.base_url,
-        config.linkedin_version,
-        config.access_token,
-        config.restli_protocol_version,
-        config.timeout,
-        config.tunnel_mode,
-    )?;
+    let client = client_from_matches(tree, matches)?;
 
     let resp = client.call(&method, path, &query, &headers, body.as_ref())?;
     let out = serde_json::json!({
@@ -635,73 +1677,1090 @@ fn handle_s3(matches: &clap::ArgMatches) -> Result<()> {
     Ok(())
 }
 
-fn handle_image(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
-    let (op, op_matches) = matches.subcommand().ok_or_else(|| anyhow!("op required"))?;
-    if op != "upload" {
-        return Err(anyhow!("unknown image op"));
-    }
-
-    let owner = op_matches
+fn handle_image_upload(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let owner = matches
         .get_one::<String>("owner")
         .ok_or_else(|| anyhow!("owner required"))?;
-    let file = op_matches
+    let file = matches
         .get_one::<String>("file")
         .ok_or_else(|| anyhow!("file required"))?;
-    let recipe = op_matches
+    let recipe = matches
         .get_one::<String>("recipe")
         .map(|s| s.as_str())
         .unwrap_or(DEFAULT_IMAGE_RECIPE);
 
-    let config = load_config(tree, matches)?;
-    let client = RestliClient::new(
-        config.base_url,
-        config.linkedin_version,
-        config.access_token,
-        config.restli_protocol_version,
-        config.timeout,
-        config.tunnel_mode,
-    )?;
+    let client = client_from_matches(tree, matches)?;
+    let owner = organizations::resolve_owner(&client, owner)?;
 
     let file = uploads::resolve_file_source(file)?;
-    let out = upload_image(&client, owner, &file, recipe)?;
+    let out = if use_media_api(tree, &client, "image", matches.get_flag("legacy_assets")) {
+        upload_image_api(&client, &owner, &file)?
+    } else {
+        upload_image(&client, &owner, &file, recipe)?
+    };
     write_json(&out, matches.get_flag("pretty"))?;
     Ok(())
 }
 
-fn handle_video(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
-    let (op, op_matches) = matches.subcommand().ok_or_else(|| anyhow!("op required"))?;
-    if op != "upload" {
-        return Err(anyhow!("unknown video op"));
-    }
+/// Images/Videos API (`resource` is `image` or `video`) unless `--legacy-assets` or
+/// the active version predates it.
+fn use_media_api(
+    tree: &CommandTree,
+    client: &RestliClient,
+    resource: &str,
+    legacy_assets: bool,
+) -> bool {
+    let version = &client.linkedin_version;
+    !legacy_assets
+        && find_op(tree, resource, "initialize-upload", version)
+            .is_some_and(|op| op.supports_version(version))
+}
 
-    let owner = op_matches
+fn handle_video_upload(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let owner = matches
         .get_one::<String>("owner")
         .ok_or_else(|| anyhow!("owner required"))?;
-    let file = op_matches
+    let file = matches
         .get_one::<String>("file")
         .ok_or_else(|| anyhow!("file required"))?;
-    let recipe = op_matches
+    let recipe = matches
         .get_one::<String>("recipe")
         .map(|s| s.as_str())
         .unwrap_or(DEFAULT_VIDEO_RECIPE);
-    let wait = op_matches.get_flag("wait");
+    let wait = matches.get_flag("wait");
 
-    let config = load_config(tree, matches)?;
-    let client = RestliClient::new(
-        config.base_url,
-        config.linkedin_version,
-        config.access_token,
-        config.restli_protocol_version,
-        config.timeout,
-        config.tunnel_mode,
-    )?;
+    // Check captions/thumbnail before anything is uploaded.
+    let captions = matches
+        .get_one::<String>("captions")
+        .map(|f| load_video_extra("captions", f))
+        .transpose()?;
+    let thumbnail = matches
+        .get_one::<String>("thumbnail")
+        .map(|f| load_video_extra("thumbnail", f))
+        .transpose()?;
+
+    let client = client_from_matches(tree, matches)?;
+    let owner = organizations::resolve_owner(&client, owner)?;
 
     let file = uploads::resolve_file_source(file)?;
-    let out = upload_video(&client, owner, &file, recipe, wait)?;
+    let out = if use_media_api(tree, &client, "video", matches.get_flag("legacy_assets")) {
+        let opts = VideoUploadOptions {
+            captions,
+            thumbnail,
+            wait,
+        };
+        upload_video_api(&client, &owner, &file, opts)?
+    } else {
+        if captions.is_some() || thumbnail.is_some() {
+            return Err(anyhow!(
+                "--captions/--thumbnail need the Videos API (drop --legacy-assets)"
+            ));
+        }
+        upload_video(&client, &owner, &file, recipe, wait)?
+    };
     write_json(&out, matches.get_flag("pretty"))?;
     Ok(())
 }
 
+/// Read and validate SRT captions or a thumbnail image.
+fn load_video_extra(kind: &str, value: &str) -> Result<Vec<u8>> {
+    let file = uploads::resolve_file_source(value)?;
+    let bytes = uploads::read_all_bytes(&file)?;
+    if kind == "captions" {
+        let text = std::str::from_utf8(&bytes)
+            .map_err(|_| anyhow!("{}: captions must be UTF-8", file.file_name))?;
+        let cues = media_checks::validate_srt(text)
+            .map_err(|e| anyhow!("{}: invalid SRT: {e}", file.file_name))?;
+        log::info!("{}: {cues} caption cues", file.file_name);
+    } else {
+        let (format, width, height) = media_checks::validate_thumbnail(&bytes)
+            .map_err(|e| anyhow!("{}: {e}", file.file_name))?;
+        log::info!("{}: {format} {width}x{height}", file.file_name);
+    }
+    Ok(bytes)
+}
+
+fn handle_document_upload(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let owner = matches
+        .get_one::<String>("owner")
+        .ok_or_else(|| anyhow!("owner required"))?;
+    let file = matches
+        .get_one::<String>("file")
+        .ok_or_else(|| anyhow!("file required"))?;
+
+    // Validate before resolving the owner so a bad file costs no API calls.
+    let file = uploads::resolve_file_source(file)?;
+    let (bytes, pages) = documents::validate(&file)?;
+
+    let client = client_from_matches(tree, matches)?;
+    let owner = organizations::resolve_owner(&client, owner)?;
+    let document = documents::upload(&client, &owner, bytes)?;
+    log::info!(
+        "uploaded {} as {document}; waiting for processing",
+        file.file_name
+    );
+    let processed =
+        documents::wait_available(&client, &document, std::time::Duration::from_secs(600))?;
+
+    let out = serde_json::json!({
+        "document": document,
+        "status": processed.get("status"),
+        "pages": pages,
+        "file": file.file_name,
+    });
+    write_json(&out, matches.get_flag("pretty"))?;
+    Ok(())
+}
+
+fn handle_conversions_upload(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let conversion = matches
+        .get_one::<String>("conversion")
+        .ok_or_else(|| anyhow!("conversion required"))?;
+    let file = matches
+        .get_one::<String>("file")
+        .ok_or_else(|| anyhow!("file required"))?;
+
+    let source = uploads::resolve_file_source(file)?;
+    let format = match matches.get_one::<String>("format") {
+        Some(format) => records::RecordFormat::parse(format)?,
+        None => records::RecordFormat::infer(&source.file_name),
+    };
+    let mapping = records::ColumnMapping::parse(
+        matches.get_one::<String>("mapping").map(String::as_str),
+        conversions::FIELDS,
+    )?;
+    let rows = records::read_records(&source.path, format)?;
+
+    let dry_run = matches.get_flag("dry_run");
+    let client = if dry_run {
+        None
+    } else {
+        Some(client_from_matches(tree, matches)?)
+    };
+
+    let opts = conversions::UploadOptions {
+        conversion: restli::ensure_urn(conversion, conversions::CONVERSION_URN),
+        batch_size: matches
+            .get_one::<usize>("batch_size")
+            .copied()
+            .unwrap_or(conversions::DEFAULT_BATCH_SIZE),
+        default_currency: matches.get_one::<String>("currency").cloned(),
+        dry_run,
+    };
+
+    let report_path = matches
+        .get_one::<String>("report")
+        .cloned()
+        .unwrap_or_else(|| source.sibling_path(".report.csv").display().to_string());
+    let mut report =
+        records::ReportWriter::create(Path::new(&report_path), conversions::REPORT_COLUMNS)?;
+    let mut summary = conversions::upload(client.as_ref(), &rows, &mapping, &opts, &mut report)?;
+    report.finish()?;
+
+    summary["report"] = Value::String(report_path);
+    write_json(&summary, matches.get_flag("pretty"))?;
+    Ok(())
+}
+
+/// Parsed input of `audience upload` / `audience sync`.
+struct SegmentListInput {
+    segment: String,
+    kind: audiences::SegmentKind,
+    rows: Vec<records::Record>,
+    mapping: records::ColumnMapping,
+    batch_size: usize,
+    dry_run: bool,
+    report_path: String,
+}
+
+fn segment_list_input(matches: &clap::ArgMatches) -> Result<SegmentListInput> {
+    let segment = matches
+        .get_one::<String>("segment")
+        .ok_or_else(|| anyhow!("segment required"))?;
+    let file = matches
+        .get_one::<String>("file")
+        .ok_or_else(|| anyhow!("file required"))?;
+    let kind = audiences::SegmentKind::parse(
+        matches
+            .get_one::<String>("type")
+            .map(String::as_str)
+            .unwrap_or("user"),
+    )?;
+
+    let source = uploads::resolve_file_source(file)?;
+    let format = match matches.get_one::<String>("format") {
+        Some(format) => records::RecordFormat::parse(format)?,
+        None => records::RecordFormat::infer(&source.file_name),
+    };
+    let mapping = records::ColumnMapping::parse(
+        matches.get_one::<String>("mapping").map(String::as_str),
+        kind.fields(),
+    )?;
+    let rows = records::read_records(&source.path, format)?;
+
+    Ok(SegmentListInput {
+        segment: segment.clone(),
+        kind,
+        rows,
+        mapping,
+        batch_size: matches
+            .get_one::<usize>("batch_size")
+            .copied()
+            .unwrap_or(audiences::DEFAULT_BATCH_SIZE),
+        dry_run: matches.get_flag("dry_run"),
+        report_path: matches
+            .get_one::<String>("report")
+            .cloned()
+            .unwrap_or_else(|| source.sibling_path(".report.csv").display().to_string()),
+    })
+}
+
+fn handle_audience_upload(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let input = segment_list_input(matches)?;
+    let client = if input.dry_run {
+        None
+    } else {
+        Some(client_from_matches(tree, matches)?)
+    };
+
+    let opts = audiences::UploadOptions {
+        segment: input.segment,
+        kind: input.kind,
+        action: matches
+            .get_one::<String>("action")
+            .cloned()
+            .unwrap_or_else(|| "ADD".to_string()),
+        batch_size: input.batch_size,
+        dry_run: input.dry_run,
+    };
+
+    let mut report =
+        records::ReportWriter::create(Path::new(&input.report_path), audiences::REPORT_COLUMNS)?;
+    let mut summary = audiences::upload(
+        client.as_ref(),
+        &input.rows,
+        &input.mapping,
+        &opts,
+        &mut report,
+    )?;
+    report.finish()?;
+
+    summary["report"] = Value::String(input.report_path);
+    write_json(&summary, matches.get_flag("pretty"))?;
+    Ok(())
+}
+
+fn handle_audience_sync(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let input = segment_list_input(matches)?;
+    let snapshot_path = match matches.get_one::<String>("snapshot") {
+        Some(path) => PathBuf::from(path),
+        None => audiences::default_snapshot_path(&input.segment, input.kind)
+            .ok_or_else(|| anyhow!("no config dir for the snapshot; pass --snapshot"))?,
+    };
+    let mut snapshot = audiences::Snapshot::load(&snapshot_path, &input.segment, input.kind)?;
+    let client = if input.dry_run {
+        None
+    } else {
+        Some(client_from_matches(tree, matches)?)
+    };
+
+    let opts = audiences::SyncOptions {
+        segment: input.segment,
+        kind: input.kind,
+        batch_size: input.batch_size,
+        dry_run: input.dry_run,
+    };
+
+    let mut report =
+        records::ReportWriter::create(Path::new(&input.report_path), audiences::REPORT_COLUMNS)?;
+    let mut summary = audiences::sync(
+        client.as_ref(),
+        &input.rows,
+        &input.mapping,
+        &opts,
+        &mut snapshot,
+        &snapshot_path,
+        &mut report,
+    )?;
+    report.finish()?;
+
+    summary["report"] = Value::String(input.report_path);
+    write_json(&summary, matches.get_flag("pretty"))?;
+    Ok(())
+}
+
+fn handle_audience_status(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let segment = matches
+        .get_one::<String>("segment")
+        .ok_or_else(|| anyhow!("segment required"))?;
+    let client = client_from_matches(tree, matches)?;
+
+    let status = if matches.get_flag("wait") {
+        let seconds = |name: &str| {
+            std::time::Duration::from_secs(matches.get_one::<u64>(name).copied().unwrap_or(30))
+        };
+        audiences::wait_until_ready(
+            &client,
+            segment,
+            seconds("wait_timeout"),
+            seconds("interval"),
+        )?
+    } else {
+        audiences::segment_status(&client, segment)?
+    };
+    write_json(&status, matches.get_flag("pretty"))?;
+    Ok(())
+}
+
+/// `--owner`, else the default ad account, as a URN.
+fn owner_urn(matches: &clap::ArgMatches) -> Result<String> {
+    let owner = match matches.get_one::<String>("owner") {
+        Some(owner) => owner.clone(),
+        None => default_account_id(matches)?
+            .ok_or_else(|| anyhow!("--owner required (or set a default ad account)"))?,
+    };
+    Ok(restli::ensure_urn(&owner, "urn:li:sponsoredAccount"))
+}
+
+fn handle_lead_form_create(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let file = matches
+        .get_one::<String>("file")
+        .ok_or_else(|| anyhow!("file required"))?;
+    let definition = leads::LeadFormDefinition::load(Path::new(file))?;
+    let body = definition.to_body(&owner_urn(matches)?)?;
+    if matches.get_flag("dry_run") {
+        write_json(&body, matches.get_flag("pretty"))?;
+        return Ok(());
+    }
+
+    let client = client_from_matches(tree, matches)?;
+    let resp = client.call(
+        "POST",
+        "/leadForms",
+        &BTreeMap::new(),
+        &BTreeMap::new(),
+        Some(&body),
+    )?;
+    write_json(
+        &unwrap_body(resp.body, &resp.headers),
+        matches.get_flag("pretty"),
+    )?;
+    Ok(())
+}
+
+fn handle_leads_export(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let output = matches
+        .get_one::<String>("output")
+        .ok_or_else(|| anyhow!("output required"))?;
+    let owner = owner_urn(matches)?;
+    let form = matches.get_one::<String>("form").cloned();
+    let state_path = match matches.get_one::<String>("state") {
+        Some(path) => PathBuf::from(path),
+        None => leads::default_watermark_path(&owner, form.as_deref())
+            .ok_or_else(|| anyhow!("no config dir for the watermark; pass --state"))?,
+    };
+    let mut watermark = leads::Watermark::load(&state_path)?;
+    let start = match matches.get_one::<String>("since") {
+        Some(since) => timeutil::parse_millis(since)?,
+        None => watermark.start().unwrap_or(0),
+    };
+
+    let client = client_from_matches(tree, matches)?;
+    let opts = leads::ExportOptions {
+        owner: owner.clone(),
+        form,
+        lead_type: matches
+            .get_one::<String>("lead_type")
+            .cloned()
+            .unwrap_or_else(|| "SPONSORED".to_string()),
+        start,
+        end: timeutil::now_millis(),
+    };
+    let fetched = leads::fetch_responses(&client, &opts)?;
+    // The lookback re-reads leads already exported; keep only unseen ones.
+    let mut responses = Vec::new();
+    for response in fetched {
+        if !watermark.seen.contains_key(leads::response_id(&response)?) {
+            responses.push(response);
+        }
+    }
+
+    let mut flattener = leads::Flattener::new(&client);
+    let rows: Vec<Value> = responses.iter().map(|r| flattener.flatten(r)).collect();
+    let mut writer = records::ReportWriter::create(Path::new(output), &flattener.columns())?;
+    for row in &rows {
+        writer.write(row)?;
+    }
+    writer.finish()?;
+
+    for response in &responses {
+        watermark.record(leads::response_id(response)?, leads::submitted_at(response));
+    }
+    watermark.prune();
+    watermark.save(&state_path)?;
+    write_json(
+        &serde_json::json!({
+            "owner": owner,
+            "responses": responses.len(),
+            "from": timeutil::format_millis(opts.start),
+            "to": timeutil::format_millis(opts.end),
+            "watermark": watermark.submitted_at.map(timeutil::format_millis),
+            "output": output,
+            "state": state_path.display().to_string(),
+        }),
+        matches.get_flag("pretty"),
+    )?;
+    Ok(())
+}
+
+fn handle_leads_forward(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let url = matches
+        .get_one::<String>("to")
+        .ok_or_else(|| anyhow!("--to required"))?;
+    let owner = owner_urn(matches)?;
+    let form = matches.get_one::<String>("form").cloned();
+    let state_path = match matches.get_one::<String>("state") {
+        Some(path) => PathBuf::from(path),
+        None => forwarding::default_state_path(&owner, form.as_deref())
+            .ok_or_else(|| anyhow!("no config dir for the state file; pass --state"))?,
+    };
+    let dead_letter = match matches.get_one::<String>("dead_letter") {
+        Some(path) => PathBuf::from(path),
+        None => state_path.with_extension("dead-letter.ndjson"),
+    };
+    let template = match matches.get_one::<String>("template") {
+        Some(raw) => {
+            let text = match raw.strip_prefix('@') {
+                Some(path) => {
+                    std::fs::read_to_string(path).with_context(|| format!("read {path}"))?
+                }
+                None => raw.clone(),
+            };
+            Some(serde_json::from_str(&text).context("invalid JSON for --template")?)
+        }
+        None => None,
+    };
+    let since = matches
+        .get_one::<String>("since")
+        .map(|v| timeutil::parse_millis(v))
+        .transpose()?;
+
+    let opts = forwarding::ForwardOptions {
+        owner,
+        form,
+        lead_type: matches
+            .get_one::<String>("lead_type")
+            .cloned()
+            .unwrap_or_else(|| "SPONSORED".to_string()),
+        since,
+        template,
+        dead_letter,
+    };
+    let sink = forwarding::Sink::new(
+        url.clone(),
+        matches
+            .get_one::<String>("secret")
+            .cloned()
+            .or_else(|| env::var("LEADS_FORWARD_SECRET").ok()),
+        matches
+            .get_one::<String>("signature_header")
+            .cloned()
+            .unwrap_or_else(|| forwarding::DEFAULT_SIGNATURE_HEADER.to_string()),
+        matches.get_one::<u32>("retries").copied().unwrap_or(3),
+    )?;
+
+    let client = client_from_matches(tree, matches)?;
+    let mut flattener = leads::Flattener::new(&client);
+    let mut state = leads::Watermark::load(&state_path)?;
+    let interval =
+        std::time::Duration::from_secs(matches.get_one::<u64>("interval").copied().unwrap_or(60));
+    let pretty = matches.get_flag("pretty");
+    loop {
+        // A failed poll (API hiccup) is logged and retried on the next tick.
+        match forwarding::poll_once(
+            &client,
+            &mut flattener,
+            &sink,
+            &opts,
+            &mut state,
+            &state_path,
+        ) {
+            Ok(summary) => write_json(&summary, pretty)?,
+            Err(err) if !matches.get_flag("once") => log::error!("poll failed: {err:#}"),
+            Err(err) => return Err(err),
+        }
+        if matches.get_flag("once") {
+            return Ok(());
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+fn handle_webhook_serve(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let secret = matches
+        .get_one::<String>("secret")
+        .cloned()
+        .or_else(|| env::var("LINKEDIN_CLIENT_SECRET").ok())
+        .ok_or_else(|| anyhow!("--secret or LINKEDIN_CLIENT_SECRET required"))?;
+    let emitter = if let Some(command) = matches.get_one::<String>("exec") {
+        webhook::Emitter::Exec(command.clone())
+    } else if let Some(path) = matches.get_one::<String>("file") {
+        webhook::Emitter::File(PathBuf::from(path))
+    } else {
+        webhook::Emitter::Stdout
+    };
+    let opts = webhook::ServeOptions {
+        secret,
+        path: matches.get_one::<String>("path").cloned(),
+        emitter,
+    };
+    let client = if matches.get_flag("enrich") {
+        Some(client_from_matches(tree, matches)?)
+    } else {
+        None
+    };
+    let addr = format!(
+        "{}:{}",
+        matches
+            .get_one::<String>("bind")
+            .map(String::as_str)
+            .unwrap_or("127.0.0.1"),
+        matches.get_one::<u16>("port").copied().unwrap_or(8080)
+    );
+    webhook::serve(&addr, &opts, client.as_ref())
+}
+
+fn handle_targeting_search(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let query = matches
+        .get_one::<String>("query")
+        .ok_or_else(|| anyhow!("query required"))?;
+    let facet = matches
+        .get_one::<String>("facet")
+        .ok_or_else(|| anyhow!("facet required"))?;
+    let limit = matches.get_one::<usize>("limit").copied().unwrap_or(20);
+
+    let client = client_from_matches(tree, matches)?;
+    let mut entities = targeting::typeahead(&client, facet, query)?;
+    entities.truncate(limit);
+
+    if matches.get_flag("json") {
+        write_json(
+            &serde_json::to_value(&entities)?,
+            matches.get_flag("pretty"),
+        )?;
+        return Ok(());
+    }
+    let width = entities.iter().map(|e| e.urn.len()).max().unwrap_or(0);
+    for entity in &entities {
+        write_stdout_line(&format!("{:<width$}  {}", entity.urn, entity.name))?;
+    }
+    Ok(())
+}
+
+/// Campaign id from the resource-level `--id` (bare id or sponsoredCampaign URN).
+fn campaign_id(matches: &clap::ArgMatches) -> Result<String> {
+    let campaign = matches
+        .get_one::<String>("resource_id")
+        .ok_or_else(|| anyhow!("--id required (campaign id)"))?;
+    Ok(campaign.rsplit(':').next().unwrap_or(campaign).to_string())
+}
+
+/// `--account`, else the default ad account, as a bare id.
+fn helper_account(matches: &clap::ArgMatches) -> Result<String> {
+    let account = match matches.get_one::<String>("account") {
+        Some(account) => account.clone(),
+        None => default_account_id(matches)?
+            .ok_or_else(|| anyhow!("--account required (or set a default ad account)"))?,
+    };
+    Ok(account.rsplit(':').next().unwrap_or(&account).to_string())
+}
+
+fn fetch_campaign(client: &RestliClient, account: &str, campaign: &str) -> Result<Value> {
+    let resp = client.call(
+        "GET",
+        &format!("/adAccounts/{account}/adCampaigns/{campaign}"),
+        &BTreeMap::new(),
+        &BTreeMap::new(),
+        None,
+    )?;
+    Ok(resp.body)
+}
+
+fn handle_accounts_list(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let client = client_from_matches(tree, matches)?;
+    let accounts = account_users::account_summaries(&client)?;
+    if matches.get_flag("json") {
+        return write_json(&Value::Array(accounts), matches.get_flag("pretty"));
+    }
+
+    let current = default_account_id(matches)?;
+    let columns = [
+        "id",
+        "name",
+        "currency",
+        "status",
+        "type",
+        "role",
+        "reference",
+    ];
+    let cell = |account: &Value, column: &str| match account.get(column) {
+        Some(Value::String(s)) => s.clone(),
+        None | Some(Value::Null) => "-".to_string(),
+        Some(other) => other.to_string(),
+    };
+    let widths: Vec<usize> = columns
+        .iter()
+        .map(|c| {
+            accounts
+                .iter()
+                .map(|a| cell(a, c).len())
+                .chain([c.len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let header: Vec<String> = columns
+        .iter()
+        .zip(&widths)
+        .map(|(c, w)| format!("{c:<w$}"))
+        .collect();
+    write_stdout_line(&format!("  {}", header.join("  ").trim_end()))?;
+    for account in &accounts {
+        let marker = if current.as_deref() == Some(cell(account, "id").as_str()) {
+            '*'
+        } else {
+            ' '
+        };
+        let row: Vec<String> = columns
+            .iter()
+            .zip(&widths)
+            .map(|(c, w)| format!("{:<w$}", cell(account, c)))
+            .collect();
+        write_stdout_line(&format!("{marker} {}", row.join("  ").trim_end()))?;
+    }
+    Ok(())
+}
+
+fn handle_accounts_use(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let wanted = matches
+        .get_one::<String>("account")
+        .ok_or_else(|| anyhow!("account required"))?;
+    let bare = wanted.rsplit(':').next().unwrap_or(wanted);
+    let (id, name) = if bare.chars().all(|c| c.is_ascii_digit()) {
+        (bare.to_string(), None)
+    } else {
+        let client = client_from_matches(tree, matches)?;
+        let accounts = account_users::account_summaries(&client)?;
+        let matching: Vec<&Value> = accounts
+            .iter()
+            .filter(|a| {
+                a.get("name")
+                    .and_then(|v| v.as_str())
+                    .is_some_and(|n| n.eq_ignore_ascii_case(wanted))
+            })
+            .collect();
+        match matching.as_slice() {
+            [account] => (
+                account["id"].as_str().unwrap_or_default().to_string(),
+                account["name"].as_str().map(str::to_string),
+            ),
+            [] => {
+                return Err(anyhow!(
+                    "no accessible ad account named {wanted:?} (see `accounts list`)"
+                ));
+            }
+            _ => {
+                let ids: Vec<&str> = matching.iter().filter_map(|a| a["id"].as_str()).collect();
+                return Err(anyhow!(
+                    "several ad accounts are named {wanted:?} ({}); use the id",
+                    ids.join(", ")
+                ));
+            }
+        }
+    };
+
+    let profile = profile_name(matches);
+    let path = profile::set_ad_account_id(&profile, &id)?;
+    if env::var("LINKEDIN_AD_ACCOUNT_ID").is_ok() {
+        log::warn!("LINKEDIN_AD_ACCOUNT_ID is set and overrides the profile default");
+    }
+    let out = serde_json::json!({
+        "profile": profile,
+        "ad_account_id": id,
+        "name": name,
+        "config": path.display().to_string(),
+    });
+    write_json(&out, matches.get_flag("pretty"))
+}
+
+fn handle_creative_preview(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let account = helper_account(matches)?;
+    let placement = matches.get_one::<String>("placement").map(String::as_str);
+    let out_dir = PathBuf::from(
+        matches
+            .get_one::<String>("out")
+            .map(String::as_str)
+            .unwrap_or("previews"),
+    );
+    let client = client_from_matches(tree, matches)?;
+
+    let mut creatives: Vec<Value> = matches
+        .get_many::<String>("creative")
+        .into_iter()
+        .flatten()
+        .map(|c| serde_json::json!({ "id": restli::ensure_urn(c, "urn:li:sponsoredCreative") }))
+        .collect();
+    let mut title = "Ad previews".to_string();
+    if let Some(campaign) = matches.get_one::<String>("campaign") {
+        let campaign = campaign.rsplit(':').next().unwrap_or(campaign);
+        creatives.extend(previews::campaign_creatives(&client, &account, campaign)?);
+        title = format!("Ad previews: campaign {campaign}");
+    }
+    if creatives.is_empty() {
+        return Err(anyhow!("--creative or --campaign required"));
+    }
+
+    let mut rendered = Vec::new();
+    for creative in &creatives {
+        let field = |name: &str| {
+            creative
+                .get(name)
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+        };
+        let Some(urn) = field("id") else {
+            continue;
+        };
+        let elements = previews::fetch_previews(&client, &account, &urn, placement)?;
+        if elements.is_empty() {
+            log::warn!("no preview returned for {urn}");
+        }
+        for element in elements {
+            let Some(html) = element.get("preview").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            rendered.push(previews::Preview {
+                creative: urn.clone(),
+                name: field("name"),
+                status: field("intendedStatus"),
+                placement: element
+                    .get("placement")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+                    .or_else(|| placement.map(str::to_string)),
+                html: html.to_string(),
+            });
+        }
+    }
+
+    let index = previews::write_gallery(&out_dir, &title, &rendered)?;
+    let out = serde_json::json!({
+        "creatives": creatives.len(),
+        "previews": rendered.len(),
+        "index": index.display().to_string(),
+    });
+    write_json(&out, matches.get_flag("pretty"))
+}
+
+/// `--partner-id`, else the partner id of the account's Insight Tag.
+fn partner_id(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<String> {
+    if let Some(id) = matches.get_one::<String>("partner_id") {
+        return Ok(id.clone());
+    }
+    let client = client_from_matches(tree, matches)?;
+    let tag = insight_tag::account_tag(&client, &helper_account(matches)?)?;
+    insight_tag::partner_id(&tag).ok_or_else(|| anyhow!("insight tag without a partner id: {tag}"))
+}
+
+fn handle_insight_tag_snippet(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let partner_id = partner_id(tree, matches)?;
+    write_stdout_line(insight_tag::snippet(&partner_id).trim_end_matches('\n'))
+}
+
+fn handle_insight_tag_verify(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let url = matches
+        .get_one::<String>("url")
+        .ok_or_else(|| anyhow!("--url required"))?;
+    let partner_id = partner_id(tree, matches)?;
+    let fetch_url = match matches
+        .get_one::<String>("fetch_base")
+        .cloned()
+        .or_else(|| env::var(insight_tag::FETCH_BASE_ENV).ok())
+    {
+        Some(base) => insight_tag::rebase_url(url, &base),
+        None => url.clone(),
+    };
+
+    let (status, html) = insight_tag::fetch_page(&fetch_url)?;
+    let mut report = insight_tag::inspect_page(&html);
+    let found = report["partner_ids"].as_array().is_some_and(|ids| {
+        ids.iter()
+            .any(|id| id.as_str() == Some(partner_id.as_str()))
+    });
+    let ok = (200..300).contains(&status) && found && report["script_loader"] == Value::Bool(true);
+    report["url"] = Value::String(url.clone());
+    report["fetched"] = Value::String(fetch_url);
+    report["http_status"] = Value::from(status);
+    report["partner_id"] = Value::String(partner_id.clone());
+    report["ok"] = Value::Bool(ok);
+    write_json(&report, matches.get_flag("pretty"))?;
+    if !ok {
+        return Err(anyhow!(
+            "insight tag with partner id {partner_id} not found on {url}"
+        ));
+    }
+    Ok(())
+}
+
+fn handle_users_grant(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let user = matches
+        .get_one::<String>("user")
+        .ok_or_else(|| anyhow!("--user required"))?;
+    let user = restli::ensure_urn(user, "urn:li:person");
+    let role = matches
+        .get_one::<String>("role")
+        .ok_or_else(|| anyhow!("--role required"))?;
+    let requested: Vec<&String> = matches
+        .get_many::<String>("accounts")
+        .into_iter()
+        .flatten()
+        .collect();
+    let dry_run = matches.get_flag("dry_run");
+    let client = client_from_matches(tree, matches)?;
+
+    let mut skipped = Vec::new();
+    let accounts: Vec<String> = if requested.iter().any(|a| a.as_str() == "all") {
+        let mut accounts = Vec::new();
+        for (account, my_role) in account_users::my_accounts(&client)? {
+            if account_users::can_manage(&my_role) {
+                accounts.push(account);
+            } else {
+                skipped.push(serde_json::json!({ "account": account, "reason": format!("your role is {my_role}") }));
+            }
+        }
+        accounts
+    } else {
+        requested
+            .iter()
+            .map(|a| restli::ensure_urn(a, "urn:li:sponsoredAccount"))
+            .collect()
+    };
+
+    let mut results = Vec::new();
+    let mut failed = 0;
+    for account in &accounts {
+        let outcome = account_users::plan(&client, account, &user, role).and_then(|change| {
+            if !dry_run && change != account_users::Change::Unchanged {
+                account_users::set_role(&client, account, &user, role)?;
+            }
+            Ok(change)
+        });
+        results.push(match outcome {
+            Ok(change) => serde_json::json!({ "account": account, "action": change.as_str() }),
+            Err(err) => {
+                failed += 1;
+                serde_json::json!({ "account": account, "error": format!("{err:#}") })
+            }
+        });
+    }
+
+    let out = serde_json::json!({
+        "user": user,
+        "role": role,
+        "dry_run": dry_run,
+        "results": results,
+        "skipped": skipped,
+    });
+    write_json(&out, matches.get_flag("pretty"))?;
+    if failed > 0 {
+        return Err(anyhow!("{failed} of {} accounts failed", accounts.len()));
+    }
+    Ok(())
+}
+
+fn handle_bid_suggest(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let account = helper_account(matches)?;
+    let client = client_from_matches(tree, matches)?;
+    let mut query = match (
+        matches.get_one::<String>("campaign"),
+        matches.get_one::<String>("targeting"),
+    ) {
+        (Some(campaign), _) => {
+            let campaign = campaign.rsplit(':').next().unwrap_or(campaign);
+            let body = fetch_campaign(&client, &account, campaign)?;
+            bidding::PricingQuery::from_campaign(&account, &body)?
+        }
+        (None, Some(file)) => {
+            let mut resolver = targeting::Resolver::new(&client);
+            bidding::PricingQuery {
+                account: account.clone(),
+                bid_type: matches
+                    .get_one::<String>("cost_type")
+                    .cloned()
+                    .ok_or_else(|| anyhow!("--cost-type required with --targeting"))?,
+                campaign_type: bidding::DEFAULT_CAMPAIGN_TYPE.to_string(),
+                criteria: targeting::compile_file(Path::new(file), &mut resolver)?,
+                daily_budget: None,
+                optimization_target: None,
+            }
+        }
+        (None, None) => return Err(anyhow!("--campaign or --targeting required")),
+    };
+    if let Some(cost_type) = matches.get_one::<String>("cost_type") {
+        query.bid_type = cost_type.clone();
+    }
+    if let Some(campaign_type) = matches.get_one::<String>("campaign_type") {
+        query.campaign_type = campaign_type.clone();
+    }
+    let pricing = bidding::budget_pricing(&client, &query)?;
+    write_json(&pricing, matches.get_flag("pretty"))
+}
+
+fn handle_campaign_explain_targeting(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let campaign = campaign_id(matches)?;
+    let client = client_from_matches(tree, matches)?;
+    let body = fetch_campaign(&client, &helper_account(matches)?, &campaign)?;
+    let name = body.get("name").and_then(|v| v.as_str()).unwrap_or("");
+    let criteria = body
+        .get("targetingCriteria")
+        .ok_or_else(|| anyhow!("campaign {campaign} has no targetingCriteria"))?;
+
+    let mut resolver = targeting::Resolver::new(&client);
+    let names = resolver.names(&targeting::criteria_urns(criteria))?;
+    resolver.save()?;
+    let count = targeting::audience_count(&client, criteria).unwrap_or_else(|err| {
+        log::warn!("audience count failed: {err:#}");
+        None
+    });
+
+    if matches.get_flag("json") {
+        let out = serde_json::json!({
+            "campaign": campaign,
+            "name": name,
+            "targetingCriteria": criteria,
+            "names": names,
+            "audienceCount": count,
+        });
+        return write_json(&out, matches.get_flag("pretty"));
+    }
+    let audience = match count {
+        Some(count) => format!(
+            "audience: {} total, {} active",
+            count.get("total").unwrap_or(&Value::Null),
+            count.get("active").unwrap_or(&Value::Null)
+        ),
+        None => "audience: unavailable (below the minimum size or not returned)".to_string(),
+    };
+    write_stdout_line(&format!(
+        "campaign {campaign}: {name}\n{}{audience}",
+        targeting::explain(criteria, &names)
+    ))
+}
+
+/// Params `build_request` skips: targeting files are compiled to `targetingCriteria`;
+/// image/video files are uploaded (owned by the body's `author`) and replaced by the
+/// media URN.
+fn resolve_caller_params(
+    tree: &CommandTree,
+    client: &RestliClient,
+    op: &Operation,
+    op_matches: &clap::ArgMatches,
+    built: &mut params::BuiltRequest,
+) -> Result<()> {
+    for param in &op.params {
+        let Some(value) = op_matches.get_one::<String>(&param_key(param)) else {
+            continue;
+        };
+        let resolved = match param.param_type.as_str() {
+            "targeting" => {
+                let mut resolver = targeting::Resolver::new(client);
+                targeting::compile_file(Path::new(value), &mut resolver)?
+            }
+            "image" | "video" => {
+                let owner = built
+                    .body
+                    .as_ref()
+                    .and_then(|b| b.get("author"))
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow!("--{} needs --author (the media owner)", param.flag))?
+                    .to_string();
+                let file = uploads::resolve_file_source(value)?;
+                if use_media_api(tree, client, &param.param_type, false) {
+                    let (uploaded, key) = if param.param_type == "image" {
+                        (upload_image_api(client, &owner, &file)?, "image")
+                    } else {
+                        let opts = VideoUploadOptions {
+                            wait: true,
+                            ..Default::default()
+                        };
+                        (upload_video_api(client, &owner, &file, opts)?, "video")
+                    };
+                    let urn = uploaded
+                        .get(key)
+                        .cloned()
+                        .ok_or_else(|| anyhow!("upload returned no {key}: {uploaded}"))?;
+                    log::info!("uploaded {value} as {urn}");
+                    params::set_param_value(&mut built.body, param, urn)?;
+                    continue;
+                }
+                let uploaded = if param.param_type == "image" {
+                    upload_image(client, &owner, &file, DEFAULT_IMAGE_RECIPE)?
+                } else {
+                    upload_video(client, &owner, &file, DEFAULT_VIDEO_RECIPE, true)?
+                };
+                let asset = uploaded
+                    .get("asset")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow!("upload returned no asset: {uploaded}"))?;
+                // Posts take the same id in the image/video namespace.
+                let id = asset.rsplit(':').next().unwrap_or(asset);
+                log::info!("uploaded {value} as {asset}");
+                Value::String(format!("urn:li:{}:{id}", param.param_type))
+            }
+            "bid" => {
+                // The campaign carries the pricing inputs and its account's currency.
+                let campaign = client
+                    .call("GET", &built.path, &BTreeMap::new(), &BTreeMap::new(), None)?
+                    .body;
+                let account = campaign
+                    .get("account")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow!("campaign has no account"))?;
+                let amount = match op_matches.get_one::<String>(&params::flag_key("clamp")) {
+                    Some(mode) => {
+                        let clamp = bidding::Clamp::parse(mode)?;
+                        // `--targeting` precedes `--bid` in the schema, so its compiled
+                        // criteria are already in the patch.
+                        let query = bidding::PricingQuery::from_update(
+                            account,
+                            &campaign,
+                            built.body.as_ref(),
+                        )?;
+                        let pricing = bidding::budget_pricing(client, &query)?;
+                        bidding::clamp_bid(value, &pricing, clamp)?
+                    }
+                    None => value.clone(),
+                };
+                let currency = account_users::account_currency(client, account)?;
+                serde_json::json!({ "amount": amount, "currencyCode": currency })
+            }
+            "clamp" => {
+                if op_matches
+                    .get_one::<String>(&params::flag_key("bid"))
+                    .is_none()
+                {
+                    return Err(anyhow!("--clamp needs --bid"));
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        params::set_param_value(&mut built.body, param, resolved)?;
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
 fn paginate_all(
     client: &RestliClient,
     method: &str,
@@ -716,17 +2775,13 @@ fn paginate_all(
     let mut items = Vec::new();
     let mut pages = 1u64;
 
-    loop {
-        if let Some(arr) = resp.body.get("elements").and_then(|v| v.as_array()) {
-            for item in arr {
-                items.push(item.clone());
-                if max_items > 0 && items.len() as u64 >= max_items {
-                    resp.body = serde_json::json!({ "elements": items });
-                    return Ok(resp);
-                }
+    while let Some(arr) = resp.body.get("elements").and_then(|v| v.as_array()) {
+        for item in arr {
+            items.push(item.clone());
+            if max_items > 0 && items.len() as u64 >= max_items {
+                resp.body = serde_json::json!({ "elements": items });
+                return Ok(resp);
             }
-        } else {
-            break;
         }
 
         let next_href = next_link_href(&resp.body);
@@ -749,10 +2804,10 @@ fn next_link_href(body: &Value) -> Option<String> {
         .and_then(|l| l.as_array())?;
     for link in links {
         let rel = link.get("rel").and_then(|v| v.as_str())?;
-        if rel == "next" {
-            if let Some(href) = link.get("href").and_then(|v| v.as_str()) {
-                return Some(href.to_string());
-            }
+        if rel == "next"
+            && let Some(href) = link.get("href").and_then(|v| v.as_str())
+        {
+            return Some(href.to_string());
         }
     }
     None
@@ -809,18 +2864,48 @@ fn json_value_to_string(value: &Value) -> Result<String> {
     }
 }
 
-fn find_op<'a>(tree: &'a CommandTree, res: &str, op: &str) -> Option<&'a Operation> {
+fn find_op<'a>(tree: &'a CommandTree, res: &str, op: &str, version: &str) -> Option<&'a Operation> {
     tree.resources
         .iter()
         .find(|r| r.name == res)
-        .and_then(|r| r.ops.iter().find(|o| o.name == op))
+        .and_then(|r| r.resolve_op(op, version))
 }
 
-fn default_account_id(resource: &str) -> Option<String> {
-    if resource != "ad-account" {
-        return None;
+/// LinkedIn-Version the CLI is being built for. Resolved before clap parsing so
+/// version-gated ops can be hidden; mirrors the precedence in `load_config`.
+fn active_linkedin_version(tree: &CommandTree) -> String {
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if let Some(value) = arg.strip_prefix("--linkedin-version=") {
+            return value.to_string();
+        }
+        if arg == "--linkedin-version"
+            && let Some(value) = args.next()
+        {
+            return value;
+        }
+    }
+    env::var("LINKEDIN_VERSION").unwrap_or_else(|_| tree.default_linkedin_version.clone())
+}
+
+/// Default ad account: LINKEDIN_AD_ACCOUNT_ID, then the active profile.
+fn default_account_id(matches: &clap::ArgMatches) -> Result<Option<String>> {
+    if let Ok(id) = env::var("LINKEDIN_AD_ACCOUNT_ID") {
+        return Ok(Some(id));
     }
-    env::var("LINKEDIN_AD_ACCOUNT_ID").ok()
+    let profile = profile::load_profile(&profile_name(matches))?;
+    Ok(profile.ad_account_id)
+}
+
+fn profile_name(matches: &clap::ArgMatches) -> String {
+    matches
+        .get_one::<String>("profile")
+        .cloned()
+        .or_else(|| env::var("LINKEDIN_PROFILE").ok())
+        .unwrap_or_else(|| profile::DEFAULT_PROFILE.to_string())
 }
 
 fn default_asset_id(resource: &str) -> Option<String> {
@@ -0,0 +1,179 @@
+use anyhow::{Result, anyhow};
+
+/// Custom video thumbnails: JPG or PNG up to 2 MB.
+pub const THUMBNAIL_MAX_BYTES: usize = 2 * 1024 * 1024;
+/// Thumbnails narrower than this look blurry in the feed (warning only).
+pub const THUMBNAIL_MIN_WIDTH: u32 = 640;
+
+/// Check SRT syntax: numbered cues, `HH:MM:SS,mmm --> HH:MM:SS,mmm` timings with the
+/// end after the start, and at least one text line per cue. Returns the cue count.
+pub fn validate_srt(text: &str) -> Result<usize> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let lines: Vec<&str> = text.lines().map(|l| l.trim_end_matches('\r')).collect();
+    let mut cues = 0;
+    let mut i = 0;
+    while i < lines.len() {
+        if lines[i].trim().is_empty() {
+            i += 1;
+            continue;
+        }
+        let line_no = i + 1;
+        if lines[i].trim().parse::<u64>().is_err() {
+            return Err(anyhow!(
+                "line {line_no}: expected a cue number, got {:?}",
+                lines[i]
+            ));
+        }
+        let timing = lines
+            .get(i + 1)
+            .ok_or_else(|| anyhow!("line {line_no}: cue has no timing line"))?;
+        let (start, end) = timing
+            .split_once("-->")
+            .ok_or_else(|| anyhow!("line {}: expected `start --> end`", line_no + 1))?;
+        let start = srt_millis(start.trim()).map_err(|e| anyhow!("line {}: {e}", line_no + 1))?;
+        // Anything after the end time is positioning, e.g. `X1:100 X2:200`.
+        let end = end.split_whitespace().next().unwrap_or("");
+        let end = srt_millis(end).map_err(|e| anyhow!("line {}: {e}", line_no + 1))?;
+        if end <= start {
+            return Err(anyhow!(
+                "line {}: cue must end after it starts",
+                line_no + 1
+            ));
+        }
+        i += 2;
+        let text_start = i;
+        while i < lines.len() && !lines[i].trim().is_empty() {
+            i += 1;
+        }
+        if i == text_start {
+            return Err(anyhow!("line {line_no}: cue has no text"));
+        }
+        cues += 1;
+    }
+    if cues == 0 {
+        return Err(anyhow!("no cues found"));
+    }
+    Ok(cues)
+}
+
+/// `HH:MM:SS,mmm` to milliseconds.
+fn srt_millis(value: &str) -> Result<u64> {
+    let invalid = || anyhow!("invalid timestamp {value:?} (expected HH:MM:SS,mmm)");
+    let (clock, millis) = value.split_once(',').ok_or_else(|| {
+        if value.contains('.') {
+            anyhow!("invalid timestamp {value:?}: SRT uses ',' before the milliseconds")
+        } else {
+            invalid()
+        }
+    })?;
+    let parts: Vec<&str> = clock.split(':').collect();
+    let [h, m, s] = parts.as_slice() else {
+        return Err(invalid());
+    };
+    let field = |v: &str, max: u64| -> Result<u64> {
+        let n: u64 = v.parse().map_err(|_| invalid())?;
+        if n > max {
+            return Err(invalid());
+        }
+        Ok(n)
+    };
+    if millis.len() != 3 {
+        return Err(invalid());
+    }
+    Ok(field(h, 99)? * 3_600_000
+        + field(m, 59)? * 60_000
+        + field(s, 59)? * 1000
+        + field(millis, 999)?)
+}
+
+/// Check a thumbnail image: JPG or PNG within the size limit. Returns the format and
+/// dimensions.
+pub fn validate_thumbnail(bytes: &[u8]) -> Result<(&'static str, u32, u32)> {
+    if bytes.len() > THUMBNAIL_MAX_BYTES {
+        return Err(anyhow!(
+            "thumbnail is {} bytes; the limit is {} MB",
+            bytes.len(),
+            THUMBNAIL_MAX_BYTES / (1024 * 1024)
+        ));
+    }
+    let (format, width, height) = if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        let dims = png_dimensions(bytes).ok_or_else(|| anyhow!("truncated PNG header"))?;
+        ("png", dims.0, dims.1)
+    } else if bytes.starts_with(&[0xff, 0xd8]) {
+        let dims = jpeg_dimensions(bytes).ok_or_else(|| anyhow!("no JPEG frame header found"))?;
+        ("jpeg", dims.0, dims.1)
+    } else {
+        return Err(anyhow!("thumbnail must be a JPG or PNG image"));
+    };
+    if width == 0 || height == 0 {
+        return Err(anyhow!("thumbnail has no pixels ({width}x{height})"));
+    }
+    if width < THUMBNAIL_MIN_WIDTH {
+        log::warn!(
+            "thumbnail is {width}x{height}; at least {THUMBNAIL_MIN_WIDTH}px wide is recommended"
+        );
+    }
+    Ok((format, width, height))
+}
+
+fn png_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    // Signature (8), IHDR length + type (8), then width and height.
+    let width = u32::from_be_bytes(bytes.get(16..20)?.try_into().ok()?);
+    let height = u32::from_be_bytes(bytes.get(20..24)?.try_into().ok()?);
+    Some((width, height))
+}
+
+fn jpeg_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let mut i = 2;
+    while i + 4 <= bytes.len() {
+        if bytes[i] != 0xff {
+            return None;
+        }
+        let marker = bytes[i + 1];
+        if marker == 0xff {
+            i += 1;
+            continue;
+        }
+        let len = u16::from_be_bytes([bytes[i + 2], bytes[i + 3]]) as usize;
+        // SOF0..SOF15, except DHT (C4), JPG (C8) and DAC (CC).
+        if (0xc0..=0xcf).contains(&marker) && !matches!(marker, 0xc4 | 0xc8 | 0xcc) {
+            let height = u16::from_be_bytes([*bytes.get(i + 5)?, *bytes.get(i + 6)?]);
+            let width = u16::from_be_bytes([*bytes.get(i + 7)?, *bytes.get(i + 8)?]);
+            return Some((width.into(), height.into()));
+        }
+        i += 2 + len;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_cues_with_bom_crlf_and_positioning() {
+        let srt = "\u{feff}1\r\n00:00:01,000 --> 00:00:02,500\r\nHello\r\n\r\n\
+            2\r\n00:00:03,000 --> 00:00:04,000 X1:10 X2:20\r\nTwo\r\nlines\r\n";
+        assert_eq!(validate_srt(srt).unwrap(), 2);
+    }
+
+    #[test]
+    fn reports_the_offending_line() {
+        let err = |srt: &str| validate_srt(srt).unwrap_err().to_string();
+        assert!(
+            err("one\n00:00:01,000 --> 00:00:02,000\nHi\n")
+                .starts_with("line 1: expected a cue number")
+        );
+        assert!(err("1\n00:00:01.000 --> 00:00:02,000\nHi\n").contains("SRT uses ','"));
+        assert!(err("1\n00:00:02,000 --> 00:00:01,000\nHi\n").starts_with("line 2: cue must end"));
+        assert!(err("1\n00:00:01,000 --> 00:00:02,000\n").starts_with("line 1: cue has no text"));
+        assert_eq!(err("\n\n"), "no cues found");
+    }
+
+    #[test]
+    fn parses_timestamps() {
+        assert_eq!(srt_millis("01:02:03,004").unwrap(), 3_723_004);
+        assert!(srt_millis("00:60:00,000").is_err());
+        assert!(srt_millis("00:00:00,5").is_err());
+    }
+}
@@ -0,0 +1,83 @@
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::account_users;
+use crate::client::RestliClient;
+use crate::restli;
+
+pub const ORGANIZATION_URN: &str = "urn:li:organization";
+/// ACL roles that may own media and post on the organization's behalf.
+const POSTER_ROLES: &[&str] = &[
+    "ADMINISTRATOR",
+    "CONTENT_ADMINISTRATOR",
+    "DIRECT_SPONSORED_CONTENT_POSTER",
+];
+
+/// Organization URN for `--owner`: a URN or numeric id, a vanity name
+/// (`linkedin.com/company/<vanity>`), or `auto` for the only organization the member
+/// can post as.
+pub fn resolve_owner(client: &RestliClient, owner: &str) -> Result<String> {
+    if owner.starts_with("urn:") {
+        return Ok(owner.to_string());
+    }
+    if !owner.is_empty() && owner.chars().all(|c| c.is_ascii_digit()) {
+        return Ok(format!("{ORGANIZATION_URN}:{owner}"));
+    }
+    if owner == "auto" {
+        let orgs = postable_organizations(client)?;
+        return match orgs.as_slice() {
+            [org] => {
+                log::info!("--owner auto: using {org}");
+                Ok(org.clone())
+            }
+            [] => Err(anyhow!(
+                "--owner auto: you have no approved admin/poster role on any organization"
+            )),
+            _ => Err(anyhow!(
+                "--owner auto: several organizations are eligible ({}); pass one",
+                orgs.join(", ")
+            )),
+        };
+    }
+    let org = by_vanity_name(client, owner)?;
+    match org.get("id") {
+        Some(Value::Number(id)) => Ok(format!("{ORGANIZATION_URN}:{id}")),
+        Some(Value::String(id)) => Ok(restli::ensure_urn(id, ORGANIZATION_URN)),
+        _ => Err(anyhow!("organization {owner:?} has no id")),
+    }
+}
+
+/// `/organizations?q=vanityName`: the organization for a vanity name.
+pub fn by_vanity_name(client: &RestliClient, vanity: &str) -> Result<Value> {
+    let mut query = BTreeMap::new();
+    query.insert("q".to_string(), "vanityName".to_string());
+    query.insert("vanityName".to_string(), vanity.to_string());
+    let resp = client.call("GET", "/organizations", &query, &BTreeMap::new(), None)?;
+    resp.body
+        .get("elements")
+        .and_then(|v| v.as_array())
+        .and_then(|v| v.first())
+        .cloned()
+        .ok_or_else(|| anyhow!("no organization with vanity name {vanity:?}"))
+}
+
+/// Organizations where the authenticated member holds an approved admin or poster role.
+pub fn postable_organizations(client: &RestliClient) -> Result<Vec<String>> {
+    let acls = account_users::paged(client, "/organizationAcls?q=roleAssignee&state=APPROVED")?;
+    let mut orgs: Vec<String> = Vec::new();
+    for acl in &acls {
+        let role = acl.get("role").and_then(|v| v.as_str()).unwrap_or("");
+        let org = acl
+            .get("organization")
+            .or_else(|| acl.get("organizationTarget"))
+            .and_then(|v| v.as_str());
+        if let Some(org) = org
+            && POSTER_ROLES.contains(&role)
+            && !orgs.iter().any(|o| o == org)
+        {
+            orgs.push(org.to_string());
+        }
+    }
+    Ok(orgs)
+}
@@ -4,6 +4,7 @@ use serde_json::Value;
 use std::collections::BTreeMap;
 
 use crate::command_tree::{Operation, ParamDef, ParamLocation};
+use crate::restli;
 
 #[derive(Debug)]
 pub struct BuiltRequest {
@@ -13,13 +14,21 @@ pub struct BuiltRequest {
     pub body: Option<Value>,
 }
 
+/// Clap id of a param: keyed by flag, since several flags may fill the same field
+/// (`--media URN` / `--image FILE`).
 pub fn param_key(param: &ParamDef) -> String {
-    format!("param__{}", param.name)
+    flag_key(&param.flag)
+}
+
+/// Clap id for a tree param flag.
+pub fn flag_key(flag: &str) -> String {
+    format!("param__{flag}")
 }
 
 pub fn build_request(
     op: &Operation,
     resource_id: Option<&str>,
+    defaults: &BTreeMap<String, String>,
     matches: &ArgMatches,
     params_json: Option<&String>,
     fields: Option<&String>,
@@ -57,31 +66,115 @@ pub fn build_request(
 
     // Merge explicit flags into query/body/headers and collect path params.
     let mut path_params = BTreeMap::new();
+    // Rest.li-encoded query params; appended to the path so they are not re-encoded.
+    let mut raw_query = Vec::new();
     for param in &op.params {
+        // Targeting files, media uploads and bids need API calls; the caller resolves them
+        // (see `set_param_value`).
+        if CALLER_RESOLVED_TYPES.contains(&param.param_type.as_str()) {
+            continue;
+        }
         let key = param_key(param);
-        if let Some(value) = matches.get_one::<String>(&key) {
+        let default = param
+            .default
+            .as_ref()
+            .and_then(|source| defaults.get(source));
+
+        if let Some(item_type) = list_item_type(&param.param_type) {
+            let mut values: Vec<String> = Vec::new();
+            if let Some(raw) = matches.get_many::<String>(&key) {
+                for value in raw {
+                    values.extend(
+                        value
+                            .split(',')
+                            .map(str::trim)
+                            .filter(|v| !v.is_empty())
+                            .map(|v| promote(item_type, v)),
+                    );
+                }
+            } else if let Some(value) = default {
+                values.push(promote(item_type, value));
+            }
+            if values.is_empty() {
+                continue;
+            }
             match param.location {
-                ParamLocation::Path => {
-                    path_params.insert(param.name.clone(), value.clone());
+                ParamLocation::Query => {
+                    raw_query.push(format!("{}={}", param.name, restli::encode_list(&values)));
+                }
+                ParamLocation::Body | ParamLocation::Patch => {
+                    let items = values
+                        .iter()
+                        .map(|v| typed_value(item_type, v))
+                        .collect::<Result<Vec<_>>>()?;
+                    set_body_field(&mut body, &param.location, &param.name, Value::Array(items))?;
+                }
+                ParamLocation::Path | ParamLocation::Header => {
+                    return Err(anyhow!(
+                        "list param --{} is only supported in query or body",
+                        param.flag
+                    ));
                 }
+            }
+            continue;
+        }
+
+        let Some(value) = matches.get_one::<String>(&key).or(default) else {
+            continue;
+        };
+        if let Some(urn_type) = union_urn_type(&param.param_type) {
+            let union = restli::urn_union(&restli::ensure_urn(value, urn_type));
+            match param.location {
                 ParamLocation::Query => {
-                    query.insert(param.name.clone(), value.clone());
+                    raw_query.push(format!("{}={}", param.name, restli::encode_value(&union)));
                 }
-                ParamLocation::Header => {
-                    headers.insert(param.name.clone(), value.clone());
+                ParamLocation::Body | ParamLocation::Patch => {
+                    set_body_field(&mut body, &param.location, &param.name, union)?;
                 }
-                ParamLocation::Body => {
-                    let obj = body.get_or_insert_with(|| Value::Object(serde_json::Map::new()));
-                    let Value::Object(map) = obj else {
-                        return Err(anyhow!("--params must be a JSON object to set body fields"));
-                    };
-                    map.insert(param.name.clone(), Value::String(value.clone()));
+                ParamLocation::Path | ParamLocation::Header => {
+                    return Err(anyhow!(
+                        "union param --{} is only supported in query or body",
+                        param.flag
+                    ));
                 }
             }
+            continue;
         }
+        match param.location {
+            ParamLocation::Path => {
+                path_params.insert(param.name.clone(), promote(&param.param_type, value));
+            }
+            ParamLocation::Query if param.param_type == "restli" => {
+                let value: Value = serde_json::from_str(value)
+                    .with_context(|| format!("invalid JSON for --{}", param.flag))?;
+                raw_query.push(format!("{}={}", param.name, restli::encode_value(&value)));
+            }
+            ParamLocation::Query => {
+                query.insert(param.name.clone(), promote(&param.param_type, value));
+            }
+            ParamLocation::Header => {
+                headers.insert(param.name.clone(), value.clone());
+            }
+            ParamLocation::Body | ParamLocation::Patch => {
+                let typed = typed_value(&param.param_type, value)
+                    .with_context(|| format!("invalid value for --{}", param.flag))?;
+                set_body_field(&mut body, &param.location, &param.name, typed)?;
+            }
+        }
+    }
+
+    if let Some(mut template) = render_body_template(op, resource_id, &path_params)? {
+        if let Some(overlay) = body.take() {
+            merge_json(&mut template, overlay);
+        }
+        body = Some(template);
     }
 
-    let path = render_path(&op.path, resource_id, &path_params)?;
+    let mut path = render_path(&op.path, resource_id, &path_params)?;
+    if !raw_query.is_empty() {
+        path.push(if path.contains('?') { '&' } else { '?' });
+        path.push_str(&raw_query.join("&"));
+    }
     Ok(BuiltRequest {
         path,
         query,
@@ -90,32 +183,205 @@ pub fn build_request(
     })
 }
 
+/// Render a path template. Placeholders are `{name}` or `{name:urn:li:type}`; the
+/// latter promotes bare ids to that URN type. Values are URL-encoded, so URN keys
+/// such as `urn:li:sponsoredCreative:123` become `urn%3Ali%3AsponsoredCreative%3A123`.
 pub fn render_path(
     template: &str,
     resource_id: Option<&str>,
     path_params: &BTreeMap<String, String>,
 ) -> Result<String> {
-    let mut out = template.to_string();
+    fill_placeholders(template, resource_id, path_params, |value| {
+        urlencoding::encode(value).into_owned()
+    })
+}
 
-    if out.contains("{id}") {
-        let id = resource_id.ok_or_else(|| anyhow!("--id required"))?;
-        out = out.replace("{id}", urlencoding::encode(id).as_ref());
+/// Fill an op's default body: string leaves may use the same placeholders as the
+/// path (unencoded). Returns `None` when the op has no body template.
+fn render_body_template(
+    op: &Operation,
+    resource_id: Option<&str>,
+    path_params: &BTreeMap<String, String>,
+) -> Result<Option<Value>> {
+    fn render(
+        value: &Value,
+        resource_id: Option<&str>,
+        path_params: &BTreeMap<String, String>,
+    ) -> Result<Value> {
+        Ok(match value {
+            Value::String(s) if s.contains('{') => Value::String(fill_placeholders(
+                s,
+                resource_id,
+                path_params,
+                str::to_string,
+            )?),
+            Value::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|v| render(v, resource_id, path_params))
+                    .collect::<Result<_>>()?,
+            ),
+            Value::Object(map) => {
+                let mut out = serde_json::Map::new();
+                for (k, v) in map {
+                    out.insert(k.clone(), render(v, resource_id, path_params)?);
+                }
+                Value::Object(out)
+            }
+            other => other.clone(),
+        })
     }
 
-    for (k, v) in path_params {
-        let needle = format!("{{{k}}}");
-        if out.contains(&needle) {
-            out = out.replace(&needle, urlencoding::encode(v).as_ref());
-        }
+    op.body
+        .as_ref()
+        .map(|template| render(template, resource_id, path_params))
+        .transpose()
+}
+
+fn fill_placeholders(
+    template: &str,
+    resource_id: Option<&str>,
+    path_params: &BTreeMap<String, String>,
+    encode: impl Fn(&str) -> String,
+) -> Result<String> {
+    let mut out = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .map(|i| start + i)
+            .ok_or_else(|| anyhow!("unterminated placeholder in template: {template}"))?;
+        out.push_str(&rest[..start]);
+
+        let spec = &rest[start + 1..end];
+        let (name, urn_type) = match spec.split_once(':') {
+            Some((name, urn_type)) => (name, Some(urn_type)),
+            None => (spec, None),
+        };
+        let value = if name == "id" {
+            resource_id.ok_or_else(|| anyhow!("--id required"))?
+        } else {
+            path_params
+                .get(name)
+                .map(String::as_str)
+                .ok_or_else(|| anyhow!("missing path parameter {name} for template: {template}"))?
+        };
+        let value = match urn_type {
+            Some(urn_type) => restli::ensure_urn(value, urn_type),
+            None => value.to_string(),
+        };
+        out.push_str(&encode(&value));
+        rest = &rest[end + 1..];
     }
 
-    if out.contains('{') || out.contains('}') {
+    if rest.contains('}') {
         return Err(anyhow!("missing path parameter for template: {template}"));
     }
-
+    out.push_str(rest);
     Ok(out)
 }
 
+/// Deep-merge `overlay` into `base`; overlay wins on conflicts.
+fn merge_json(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (k, v) in overlay {
+                match base.get_mut(&k) {
+                    Some(existing) => merge_json(existing, v),
+                    None => {
+                        base.insert(k, v);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Item type of a `list<T>` param type.
+fn list_item_type(param_type: &str) -> Option<&str> {
+    param_type
+        .strip_prefix("list<")
+        .and_then(|v| v.strip_suffix('>'))
+}
+
+/// Default URN type of a `union<urn:li:type>` param: any URN is accepted and sent as
+/// the union member named after its entity type; bare ids get the default type.
+fn union_urn_type(param_type: &str) -> Option<&str> {
+    param_type
+        .strip_prefix("union<")
+        .and_then(|v| v.strip_suffix('>'))
+}
+
+/// URN-typed params (`urn:li:sponsoredCampaign`) accept bare ids.
+fn promote(param_type: &str, value: &str) -> String {
+    if param_type.starts_with("urn:") {
+        restli::ensure_urn(value, param_type)
+    } else {
+        value.to_string()
+    }
+}
+
+fn typed_value(param_type: &str, value: &str) -> Result<Value> {
+    match param_type {
+        "integer" => Ok(Value::from(value.parse::<i64>()?)),
+        "number" => Ok(Value::from(value.parse::<f64>()?)),
+        "boolean" => Ok(Value::Bool(value.parse::<bool>()?)),
+        "json" => serde_json::from_str(value).context("invalid JSON"),
+        _ => Ok(Value::String(promote(param_type, value))),
+    }
+}
+
+/// Param types `build_request` leaves to the caller.
+pub const CALLER_RESOLVED_TYPES: &[&str] = &["targeting", "image", "video", "bid", "clamp"];
+
+/// Set a pre-resolved body/patch param on a built request.
+pub fn set_param_value(body: &mut Option<Value>, param: &ParamDef, value: Value) -> Result<()> {
+    match param.location {
+        ParamLocation::Body | ParamLocation::Patch => {
+            set_body_field(body, &param.location, &param.name, value)
+        }
+        _ => Err(anyhow!(
+            "--{} is only supported in body or patch",
+            param.flag
+        )),
+    }
+}
+
+/// Set a (dotted) body field; `patch` params land under `patch.$set`.
+fn set_body_field(
+    body: &mut Option<Value>,
+    location: &ParamLocation,
+    name: &str,
+    value: Value,
+) -> Result<()> {
+    let obj = body.get_or_insert_with(|| Value::Object(serde_json::Map::new()));
+    let mut segments: Vec<&str> = Vec::new();
+    if matches!(location, ParamLocation::Patch) {
+        segments.extend(["patch", "$set"]);
+    }
+    segments.extend(name.split('.'));
+
+    let (last, parents) = segments
+        .split_last()
+        .ok_or_else(|| anyhow!("empty body field name"))?;
+    let mut cur = obj;
+    for segment in parents {
+        let Value::Object(map) = cur else {
+            return Err(anyhow!("--params must be a JSON object to set body fields"));
+        };
+        cur = map
+            .entry(segment.to_string())
+            .or_insert_with(|| Value::Object(serde_json::Map::new()));
+    }
+    let Value::Object(map) = cur else {
+        return Err(anyhow!("--params must be a JSON object to set body fields"));
+    };
+    map.insert(last.to_string(), value);
+    Ok(())
+}
+
 fn json_value_to_string(value: &Value) -> Result<String> {
     match value {
         Value::String(v) => Ok(v.clone()),
@@ -0,0 +1,204 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::client::RestliClient;
+use crate::restli;
+
+/// One rendered preview of a creative.
+pub struct Preview {
+    pub creative: String,
+    pub name: Option<String>,
+    pub status: Option<String>,
+    pub placement: Option<String>,
+    /// The `preview` markup returned by LinkedIn (usually an `<iframe>`).
+    pub html: String,
+}
+
+/// `/adPreviews?q=creative` for one creative, optionally for a single placement.
+pub fn fetch_previews(
+    client: &RestliClient,
+    account: &str,
+    creative: &str,
+    placement: Option<&str>,
+) -> Result<Vec<Value>> {
+    let mut path = format!(
+        "/adPreviews?q=creative&creative={}&account={}",
+        restli::escape(&restli::ensure_urn(creative, "urn:li:sponsoredCreative")),
+        restli::escape(&restli::ensure_urn(account, "urn:li:sponsoredAccount")),
+    );
+    if let Some(placement) = placement {
+        path.push_str(&format!("&placement={}", restli::escape(placement)));
+    }
+    let resp = client.call("GET", &path, &BTreeMap::new(), &BTreeMap::new(), None)?;
+    Ok(resp
+        .body
+        .get("elements")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default())
+}
+
+/// Creatives of a campaign (follows `metadata.nextPageToken`).
+pub fn campaign_creatives(
+    client: &RestliClient,
+    account: &str,
+    campaign: &str,
+) -> Result<Vec<Value>> {
+    let base = format!(
+        "/adAccounts/{account}/creatives?q=criteria&campaigns={}&pageSize=100",
+        restli::encode_list([restli::ensure_urn(campaign, "urn:li:sponsoredCampaign")])
+    );
+    let mut creatives = Vec::new();
+    let mut token: Option<String> = None;
+    loop {
+        let path = match &token {
+            Some(token) => format!("{base}&pageToken={}", restli::escape(token)),
+            None => base.clone(),
+        };
+        let resp = client.call("GET", &path, &BTreeMap::new(), &BTreeMap::new(), None)?;
+        creatives.extend(
+            resp.body
+                .get("elements")
+                .and_then(|v| v.as_array())
+                .cloned()
+                .unwrap_or_default(),
+        );
+        token = resp
+            .body
+            .pointer("/metadata/nextPageToken")
+            .and_then(|v| v.as_str())
+            .filter(|t| !t.is_empty())
+            .map(str::to_string);
+        if token.is_none() {
+            return Ok(creatives);
+        }
+    }
+}
+
+/// Write one page per preview plus `index.html` showing them all; returns the index path.
+pub fn write_gallery(dir: &Path, title: &str, previews: &[Preview]) -> Result<PathBuf> {
+    fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    let mut cards = String::new();
+    for (preview, file) in previews.iter().zip(file_names(previews)) {
+        let heading = preview.name.as_deref().unwrap_or(&preview.creative);
+        let page = format!(
+            "<!doctype html>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<body>\n{}\n</body>\n",
+            escape(heading),
+            preview.html
+        );
+        let path = dir.join(&file);
+        fs::write(&path, page).with_context(|| format!("write {}", path.display()))?;
+
+        let meta: Vec<String> = [
+            Some(preview.creative.as_str()),
+            preview.status.as_deref(),
+            preview.placement.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(escape)
+        .collect();
+        cards.push_str(&format!(
+            "<section>\n<h2><a href=\"{file}\">{}</a></h2>\n<p>{}</p>\n{}\n</section>\n",
+            escape(heading),
+            meta.join(" &middot; "),
+            preview.html
+        ));
+    }
+    let index = format!(
+        "<!doctype html>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n\
+         body {{ font-family: sans-serif; margin: 2em; }}\n\
+         main {{ display: flex; flex-wrap: wrap; gap: 2em; }}\n\
+         section {{ max-width: 560px; }}\n\
+         h2 {{ font-size: 1em; }}\n\
+         p {{ color: #666; font-size: .85em; }}\n\
+         </style>\n<h1>{title}</h1>\n<main>\n{cards}</main>\n",
+        title = escape(title),
+    );
+    let path = dir.join("index.html");
+    fs::write(&path, index).with_context(|| format!("write {}", path.display()))?;
+    Ok(path)
+}
+
+/// One file name per preview; previews that would share a name (several previews of a
+/// creative without a placement) get `-1`, `-2`, ... in order.
+fn file_names(previews: &[Preview]) -> Vec<String> {
+    let stems: Vec<String> = previews.iter().map(file_stem).collect();
+    let mut seen: BTreeMap<&str, usize> = BTreeMap::new();
+    stems
+        .iter()
+        .map(|stem| {
+            if stems.iter().filter(|other| *other == stem).count() == 1 {
+                return format!("{stem}.html");
+            }
+            let n = seen.entry(stem).or_default();
+            *n += 1;
+            format!("{stem}-{n}.html")
+        })
+        .collect()
+}
+
+/// `<creative id>[-<placement>]`
+fn file_stem(preview: &Preview) -> String {
+    let id = preview
+        .creative
+        .rsplit(':')
+        .next()
+        .unwrap_or(&preview.creative);
+    let safe = |s: &str| -> String {
+        s.chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect()
+    };
+    match &preview.placement {
+        Some(placement) => format!("{}-{}", safe(id), safe(placement)),
+        None => safe(id),
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preview(creative: &str, placement: Option<&str>, html: &str) -> Preview {
+        Preview {
+            creative: creative.to_string(),
+            name: None,
+            status: None,
+            placement: placement.map(str::to_string),
+            html: html.to_string(),
+        }
+    }
+
+    #[test]
+    fn previews_sharing_a_name_get_numbered_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let previews = [
+            preview("urn:li:sponsoredCreative:7", None, "first"),
+            preview("urn:li:sponsoredCreative:7", None, "second"),
+            preview("urn:li:sponsoredCreative:7", Some("LINKEDIN_FEED"), "feed"),
+            preview("urn:li:sponsoredCreative:8", None, "other"),
+        ];
+        assert_eq!(
+            file_names(&previews),
+            ["7-1.html", "7-2.html", "7-LINKEDIN_FEED.html", "8.html"]
+        );
+
+        write_gallery(dir.path(), "Campaign", &previews).unwrap();
+        let read = |name: &str| fs::read_to_string(dir.path().join(name)).unwrap();
+        assert!(read("7-1.html").contains("first"));
+        assert!(read("7-2.html").contains("second"));
+        assert!(read("index.html").contains("href=\"7-2.html\""));
+    }
+}
@@ -0,0 +1,83 @@
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ProfileConfig {
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+    /// Keys this version does not know, kept when the file is rewritten.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct Profile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ad_account_id: Option<String>,
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+/// Config directory: LINKEDIN_ADS_CONFIG_DIR, then $XDG_CONFIG_HOME/linkedin-ads,
+/// then ~/.config/linkedin-ads.
+pub fn config_dir() -> Option<PathBuf> {
+    if let Ok(dir) = env::var("LINKEDIN_ADS_CONFIG_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    if let Ok(dir) = env::var("XDG_CONFIG_HOME") {
+        return Some(PathBuf::from(dir).join("linkedin-ads"));
+    }
+    env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join(".config").join("linkedin-ads"))
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("config.json"))
+}
+
+pub fn load_config() -> Result<ProfileConfig> {
+    let Some(path) = config_path() else {
+        return Ok(ProfileConfig::default());
+    };
+    if !path.exists() {
+        return Ok(ProfileConfig::default());
+    }
+    let raw = std::fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+    serde_json::from_str(&raw).with_context(|| format!("invalid JSON in {}", path.display()))
+}
+
+pub fn load_profile(name: &str) -> Result<Profile> {
+    Ok(load_config()?.profiles.remove(name).unwrap_or_default())
+}
+
+/// Write the config file (temp file + rename).
+pub fn save_config(config: &ProfileConfig) -> Result<PathBuf> {
+    let path = config_path().ok_or_else(|| anyhow!("no config directory (set HOME)"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_vec_pretty(config)?)
+        .with_context(|| format!("write {}", tmp.display()))?;
+    fs::rename(&tmp, &path).with_context(|| format!("write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Set the default ad account of profile `name`, creating the profile if needed.
+pub fn set_ad_account_id(name: &str, account_id: &str) -> Result<PathBuf> {
+    let mut config = load_config()?;
+    config
+        .profiles
+        .entry(name.to_string())
+        .or_default()
+        .ad_account_id = Some(account_id.to_string());
+    save_config(&config)
+}
@@ -0,0 +1,254 @@
+use anyhow::{Context, Result, anyhow};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordFormat {
+    Csv,
+    Ndjson,
+}
+
+impl RecordFormat {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "csv" => Ok(Self::Csv),
+            "ndjson" | "jsonl" => Ok(Self::Ndjson),
+            other => Err(anyhow!("invalid format {other} (expected: csv|ndjson)")),
+        }
+    }
+
+    /// Pick a format from the file extension; CSV unless it looks like NDJSON.
+    pub fn infer(name: &str) -> Self {
+        let lower = name.to_ascii_lowercase();
+        if lower.ends_with(".ndjson") || lower.ends_with(".jsonl") || lower.ends_with(".json") {
+            Self::Ndjson
+        } else {
+            Self::Csv
+        }
+    }
+}
+
+/// One input row; `row` is 1-based and excludes the CSV header.
+#[derive(Debug, Clone)]
+pub struct Record {
+    pub row: usize,
+    pub fields: BTreeMap<String, String>,
+}
+
+impl Record {
+    /// Non-empty, trimmed value of `column`.
+    pub fn get(&self, column: &str) -> Option<&str> {
+        self.fields
+            .get(column)
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+    }
+}
+
+pub fn read_records(path: &Path, format: RecordFormat) -> Result<Vec<Record>> {
+    match format {
+        RecordFormat::Csv => read_csv(path),
+        RecordFormat::Ndjson => read_ndjson(path),
+    }
+}
+
+fn read_csv(path: &Path) -> Result<Vec<Record>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(path)
+        .with_context(|| format!("open {}", path.display()))?;
+    let headers: Vec<String> = reader
+        .headers()
+        .context("read CSV header")?
+        .iter()
+        .map(|h| h.trim().trim_start_matches('\u{feff}').to_string())
+        .collect();
+
+    let mut out = Vec::new();
+    for (i, row) in reader.records().enumerate() {
+        let row = row.with_context(|| format!("read CSV row {}", i + 1))?;
+        let mut fields = BTreeMap::new();
+        for (header, value) in headers.iter().zip(row.iter()) {
+            fields.insert(header.clone(), value.to_string());
+        }
+        out.push(Record { row: i + 1, fields });
+    }
+    Ok(out)
+}
+
+fn read_ndjson(path: &Path) -> Result<Vec<Record>> {
+    let file = File::open(path).with_context(|| format!("open {}", path.display()))?;
+    let mut out = Vec::new();
+    let mut row = 0;
+    for line in BufReader::new(file).lines() {
+        let line = line.context("read NDJSON line")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        row += 1;
+        let value: Value =
+            serde_json::from_str(&line).with_context(|| format!("invalid JSON on row {row}"))?;
+        let Value::Object(map) = value else {
+            return Err(anyhow!("NDJSON row {row} must be a JSON object"));
+        };
+        let mut fields = BTreeMap::new();
+        for (k, v) in map {
+            match v {
+                Value::Null => {}
+                Value::String(s) => {
+                    fields.insert(k, s);
+                }
+                other => {
+                    fields.insert(k, other.to_string());
+                }
+            }
+        }
+        out.push(Record { row, fields });
+    }
+    Ok(out)
+}
+
+/// Logical field -> input column. Unmapped fields read the column of the same name.
+#[derive(Debug, Default)]
+pub struct ColumnMapping {
+    columns: BTreeMap<String, String>,
+}
+
+impl ColumnMapping {
+    /// Parse `--mapping`: a JSON object, or `@path` to a JSON file. Keys must be in `fields`.
+    pub fn parse(raw: Option<&str>, fields: &[&str]) -> Result<Self> {
+        let Some(raw) = raw else {
+            return Ok(Self::default());
+        };
+        let text = match raw.strip_prefix('@') {
+            Some(path) => std::fs::read_to_string(path).with_context(|| format!("read {path}"))?,
+            None => raw.to_string(),
+        };
+        let value: Value = serde_json::from_str(&text).context("invalid JSON for --mapping")?;
+        let Value::Object(map) = value else {
+            return Err(anyhow!("--mapping must be a JSON object"));
+        };
+        let mut columns = BTreeMap::new();
+        for (field, column) in map {
+            if !fields.contains(&field.as_str()) {
+                return Err(anyhow!(
+                    "unknown mapping field {field} (expected one of: {})",
+                    fields.join(", ")
+                ));
+            }
+            let Value::String(column) = column else {
+                return Err(anyhow!("mapping for {field} must be a column name"));
+            };
+            columns.insert(field, column);
+        }
+        Ok(Self { columns })
+    }
+
+    pub fn get<'a>(&self, record: &'a Record, field: &str) -> Option<&'a str> {
+        let column = self.columns.get(field).map(String::as_str).unwrap_or(field);
+        record.get(column)
+    }
+}
+
+/// Per-row result report, CSV or NDJSON depending on the file extension.
+pub struct ReportWriter {
+    columns: Vec<String>,
+    sink: ReportSink,
+}
+
+enum ReportSink {
+    Csv(Box<csv::Writer<File>>),
+    Ndjson(BufWriter<File>),
+}
+
+impl ReportWriter {
+    pub fn create(path: &Path, columns: &[&str]) -> Result<Self> {
+        let file = File::create(path).with_context(|| format!("create {}", path.display()))?;
+        let columns: Vec<String> = columns.iter().map(|c| c.to_string()).collect();
+        let name = path.to_string_lossy();
+        let sink = match RecordFormat::infer(&name) {
+            RecordFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(file);
+                writer
+                    .write_record(&columns)
+                    .context("write report header")?;
+                ReportSink::Csv(Box::new(writer))
+            }
+            RecordFormat::Ndjson => ReportSink::Ndjson(BufWriter::new(file)),
+        };
+        Ok(Self { columns, sink })
+    }
+
+    pub fn write(&mut self, row: &Value) -> Result<()> {
+        match &mut self.sink {
+            ReportSink::Csv(writer) => {
+                let cells: Vec<String> = self
+                    .columns
+                    .iter()
+                    .map(|c| match row.get(c) {
+                        None | Some(Value::Null) => String::new(),
+                        Some(Value::String(s)) => s.clone(),
+                        Some(other) => other.to_string(),
+                    })
+                    .collect();
+                writer.write_record(&cells).context("write report row")?;
+            }
+            ReportSink::Ndjson(writer) => {
+                serde_json::to_writer(&mut *writer, row)?;
+                writer.write_all(b"\n").context("write report row")?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<()> {
+        match self.sink {
+            ReportSink::Csv(mut writer) => writer.flush().context("flush report")?,
+            ReportSink::Ndjson(mut writer) => writer.flush().context("flush report")?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIELDS: &[&str] = &["email", "first_name"];
+
+    fn record(fields: &[(&str, &str)]) -> Record {
+        Record {
+            row: 1,
+            fields: fields
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn mapping_renames_columns_and_defaults_to_field_names() {
+        let mapping = ColumnMapping::parse(Some(r#"{"email": "E-Mail"}"#), FIELDS).unwrap();
+        let row = record(&[("E-Mail", " a@example.com "), ("first_name", "Ada")]);
+        assert_eq!(mapping.get(&row, "email"), Some("a@example.com"));
+        assert_eq!(mapping.get(&row, "first_name"), Some("Ada"));
+    }
+
+    #[test]
+    fn mapping_absent_is_identity() {
+        let mapping = ColumnMapping::parse(None, FIELDS).unwrap();
+        assert_eq!(mapping.get(&record(&[("email", "x")]), "email"), Some("x"));
+    }
+
+    #[test]
+    fn mapping_rejects_unknown_fields_and_non_strings() {
+        let err = ColumnMapping::parse(Some(r#"{"phone": "p"}"#), FIELDS).unwrap_err();
+        assert!(err.to_string().contains("unknown mapping field phone"));
+        assert!(ColumnMapping::parse(Some(r#"{"email": 1}"#), FIELDS).is_err());
+        assert!(ColumnMapping::parse(Some(r#"["email"]"#), FIELDS).is_err());
+        assert!(ColumnMapping::parse(Some("{"), FIELDS).is_err());
+    }
+}
@@ -0,0 +1,54 @@
+use serde_json::Value;
+
+/// Rest.li 2.0 URL encoding of a JSON value, for query params that carry
+/// structured data (`List(...)`, `(key:value)`). Leaf values are percent-encoded
+/// so reserved characters (`(),:'`) inside URNs survive; the result must be sent
+/// as-is, not re-encoded.
+pub fn encode_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Bool(v) => v.to_string(),
+        Value::Number(v) => v.to_string(),
+        Value::String(v) => escape(v),
+        Value::Array(items) => {
+            let inner: Vec<String> = items.iter().map(encode_value).collect();
+            format!("List({})", inner.join(","))
+        }
+        Value::Object(map) => {
+            let inner: Vec<String> = map
+                .iter()
+                .map(|(k, v)| format!("{}:{}", escape(k), encode_value(v)))
+                .collect();
+            format!("({})", inner.join(","))
+        }
+    }
+}
+
+pub fn encode_list<I, S>(items: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let inner: Vec<String> = items.into_iter().map(|v| escape(v.as_ref())).collect();
+    format!("List({})", inner.join(","))
+}
+
+pub fn escape(value: &str) -> String {
+    urlencoding::encode(value).into_owned()
+}
+
+/// Promote a bare id to `{urn_type}:{id}`; values that already are URNs pass through.
+pub fn ensure_urn(value: &str, urn_type: &str) -> String {
+    if value.starts_with("urn:") {
+        value.to_string()
+    } else {
+        format!("{urn_type}:{value}")
+    }
+}
+
+/// Rest.li union member for a URN, keyed by its entity type:
+/// `urn:li:organization:1` -> `{"organization": "urn:li:organization:1"}`.
+pub fn urn_union(urn: &str) -> Value {
+    let member = urn.split(':').nth(2).unwrap_or(urn);
+    serde_json::json!({ member: urn })
+}
@@ -0,0 +1,596 @@
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::client::RestliClient;
+use crate::profile;
+use crate::restli;
+
+pub const FACET_URN: &str = "urn:li:adTargetingFacet";
+const URN_BATCH_SIZE: usize = 50;
+/// Facets small enough to list in full when typeahead finds no exact match.
+const ENUMERABLE_FACETS: &[&str] = &["seniorities", "jobFunctions", "industries"];
+
+/// A targeting entity (location, title, industry, ...) and its display name.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Entity {
+    pub urn: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub facet: Option<String>,
+}
+
+/// `titles` -> `urn:li:adTargetingFacet:titles`. DSL spellings such as `companies` or
+/// `job_functions` map to the API facet names (`employers`, `jobFunctions`).
+pub fn facet_urn(facet: &str) -> String {
+    if facet.starts_with("urn:") {
+        return facet.to_string();
+    }
+    let name = match facet {
+        "companies" => "employers".to_string(),
+        "audiences" | "matched_audiences" => "audienceMatchingSegments".to_string(),
+        "languages" => "interfaceLocales".to_string(),
+        other => camel_case(other),
+    };
+    format!("{FACET_URN}:{name}")
+}
+
+fn camel_case(value: &str) -> String {
+    let mut out = String::new();
+    let mut upper = false;
+    for c in value.chars() {
+        if c == '_' || c == '-' {
+            upper = true;
+        } else if upper {
+            out.push(c.to_ascii_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Entities matching `query` within `facet`, best match first.
+pub fn typeahead(client: &RestliClient, facet: &str, query: &str) -> Result<Vec<Entity>> {
+    let mut params = BTreeMap::new();
+    params.insert("q".to_string(), "typeahead".to_string());
+    params.insert("queryVersion".to_string(), "QUERY_USES_URNS".to_string());
+    params.insert("facet".to_string(), facet_urn(facet));
+    params.insert("query".to_string(), query.to_string());
+    let resp = client.call(
+        "GET",
+        "/adTargetingEntities",
+        &params,
+        &BTreeMap::new(),
+        None,
+    )?;
+    Ok(parse_entities(&resp.body))
+}
+
+/// Entities for known URNs (`q=urns`).
+pub fn entities_by_urn<S: AsRef<str>>(client: &RestliClient, urns: &[S]) -> Result<Vec<Entity>> {
+    let path = format!(
+        "/adTargetingEntities?q=urns&queryVersion=QUERY_USES_URNS&urns={}",
+        restli::encode_list(urns)
+    );
+    let resp = client.call("GET", &path, &BTreeMap::new(), &BTreeMap::new(), None)?;
+    Ok(parse_entities(&resp.body))
+}
+
+/// `/audienceCounts` for `criteria`: `{"total": .., "active": ..}`, or `None` when
+/// LinkedIn withholds the count (audiences under its minimum size).
+pub fn audience_count(client: &RestliClient, criteria: &Value) -> Result<Option<Value>> {
+    let path = format!(
+        "/audienceCounts?q=targetingCriteriaV2&targetingCriteria={}",
+        restli::encode_value(criteria)
+    );
+    let resp = client.call("GET", &path, &BTreeMap::new(), &BTreeMap::new(), None)?;
+    Ok(resp
+        .body
+        .get("elements")
+        .and_then(|v| v.as_array())
+        .and_then(|v| v.first())
+        .cloned())
+}
+
+/// Every entity of a facet (for small facets such as seniorities or job functions).
+pub fn facet_entities(client: &RestliClient, facet: &str) -> Result<Vec<Entity>> {
+    let mut params = BTreeMap::new();
+    params.insert("q".to_string(), "adTargetingFacet".to_string());
+    params.insert("queryVersion".to_string(), "QUERY_USES_URNS".to_string());
+    params.insert("facet".to_string(), facet_urn(facet));
+    let resp = client.call(
+        "GET",
+        "/adTargetingEntities",
+        &params,
+        &BTreeMap::new(),
+        None,
+    )?;
+    Ok(parse_entities(&resp.body))
+}
+
+/// Name -> entity lookups per facet, persisted in `<config dir>/cache/targeting-entities.json`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct EntityCache {
+    #[serde(default)]
+    facets: BTreeMap<String, BTreeMap<String, Entity>>,
+}
+
+/// Resolves entity names to URNs through the typeahead API, backed by the local cache.
+pub struct Resolver<'a> {
+    client: &'a RestliClient,
+    cache: EntityCache,
+    cache_path: Option<PathBuf>,
+    dirty: bool,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(client: &'a RestliClient) -> Self {
+        let cache_path =
+            profile::config_dir().map(|dir| dir.join("cache").join("targeting-entities.json"));
+        let cache = cache_path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        Self {
+            client,
+            cache,
+            cache_path,
+            dirty: false,
+        }
+    }
+
+    /// URN for `value` in `facet`; values that already are URNs pass through. Only an
+    /// exact (case-insensitive) name match is accepted, and only when no other entity
+    /// shares that name.
+    pub fn resolve(&mut self, facet: &str, value: &str) -> Result<String> {
+        if value.starts_with("urn:") {
+            return Ok(value.to_string());
+        }
+        let facet = facet_urn(facet);
+        let key = value.trim().to_lowercase();
+        if let Some(entity) = self.cache.facets.get(&facet).and_then(|f| f.get(&key)) {
+            return Ok(entity.urn.clone());
+        }
+
+        let mut candidates = typeahead(self.client, &facet, value)?;
+        let enumerable = ENUMERABLE_FACETS
+            .iter()
+            .any(|name| facet == format!("{FACET_URN}:{name}"));
+        if enumerable && !candidates.iter().any(|e| e.name.to_lowercase() == key) {
+            candidates.extend(facet_entities(self.client, &facet)?);
+        }
+        // Distinct entities per lowercased name, best-ranked first. Names shared by
+        // several entities (same-named employers, cities) are never cached.
+        let mut by_name: BTreeMap<String, Vec<&Entity>> = BTreeMap::new();
+        for entity in &candidates {
+            let same = by_name.entry(entity.name.to_lowercase()).or_default();
+            if !same.iter().any(|e| e.urn == entity.urn) {
+                same.push(entity);
+            }
+        }
+        let cached = self.cache.facets.entry(facet.clone()).or_default();
+        for (name, entities) in &by_name {
+            match entities.as_slice() {
+                [entity] => {
+                    cached.insert(name.clone(), (*entity).clone());
+                }
+                _ => {
+                    cached.remove(name);
+                }
+            }
+        }
+        self.dirty = true;
+
+        match by_name.get(&key).map(Vec::as_slice) {
+            Some([entity]) => Ok(entity.urn.clone()),
+            Some(entities) if !entities.is_empty() => {
+                let matches: Vec<String> = entities
+                    .iter()
+                    .map(|e| format!("{} ({})", e.name, e.urn))
+                    .collect();
+                Err(anyhow!(
+                    "{value:?} matches several {} entities, pass the URN instead: {}",
+                    facet.trim_start_matches("urn:li:adTargetingFacet:"),
+                    matches.join(", ")
+                ))
+            }
+            _ => {
+                let suggestions: Vec<String> = candidates
+                    .iter()
+                    .take(5)
+                    .map(|e| format!("{} ({})", e.name, e.urn))
+                    .collect();
+                Err(anyhow!(
+                    "no exact {} match for {value:?}{}",
+                    facet.trim_start_matches("urn:li:adTargetingFacet:"),
+                    if suggestions.is_empty() {
+                        String::new()
+                    } else {
+                        format!("; did you mean: {}", suggestions.join(", "))
+                    }
+                ))
+            }
+        }
+    }
+
+    /// Display names for `urns`, from the cache or the entities API. URNs the API does
+    /// not know are left out.
+    pub fn names(&mut self, urns: &[String]) -> Result<BTreeMap<String, String>> {
+        let mut names: BTreeMap<String, String> = self
+            .cache
+            .facets
+            .values()
+            .flat_map(|facet| facet.values())
+            .filter(|entity| urns.contains(&entity.urn))
+            .map(|entity| (entity.urn.clone(), entity.name.clone()))
+            .collect();
+        let missing: Vec<&String> = urns.iter().filter(|u| !names.contains_key(*u)).collect();
+        for chunk in missing.chunks(URN_BATCH_SIZE) {
+            for entity in entities_by_urn(self.client, chunk)? {
+                names.insert(entity.urn.clone(), entity.name.clone());
+                if let Some(facet) = &entity.facet {
+                    self.cache
+                        .facets
+                        .entry(facet.clone())
+                        .or_default()
+                        .insert(entity.name.to_lowercase(), entity.clone());
+                    self.dirty = true;
+                }
+            }
+        }
+        Ok(names)
+    }
+
+    /// Persist newly resolved names.
+    pub fn save(&self) -> Result<()> {
+        let (Some(path), true) = (&self.cache_path, self.dirty) else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+        }
+        fs::write(path, serde_json::to_vec(&self.cache)?)
+            .with_context(|| format!("write {}", path.display()))
+    }
+}
+
+/// Compile a targeting file (YAML) into `targetingCriteria`.
+///
+/// ```yaml
+/// locations: [United States, Canada]     # each facet is one AND clause, values ORed
+/// titles: [CTO, "VP Engineering"]
+/// any:                                   # facets ORed within one AND clause
+///   - { skills: [Rust], titles: [Software Engineer] }
+/// exclude:
+///   industries: [Staffing and Recruiting]
+/// exclude.employers: [urn:li:organization:1337]
+/// ```
+pub fn compile_file(path: &Path, resolver: &mut Resolver) -> Result<Value> {
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    let spec: Value = serde_norway::from_str(&raw)
+        .with_context(|| format!("invalid targeting file {}", path.display()))?;
+    // Keep lookups made so far even when a later name fails to resolve.
+    let criteria = compile(&spec, resolver);
+    resolver.save()?;
+    criteria
+}
+
+pub fn compile(spec: &Value, resolver: &mut Resolver) -> Result<Value> {
+    let Value::Object(spec) = spec else {
+        return Err(anyhow!("targeting must be a mapping of facet -> values"));
+    };
+    let mut and = Vec::new();
+    let mut exclude = serde_json::Map::new();
+
+    for (key, value) in spec {
+        match key.as_str() {
+            "include" => {
+                let Value::Object(facets) = value else {
+                    return Err(anyhow!("include must be a mapping of facet -> values"));
+                };
+                for (facet, values) in facets {
+                    and.push(or_clause(&[(facet, values)], resolver)?);
+                }
+            }
+            "any" => {
+                let Value::Array(groups) = value else {
+                    return Err(anyhow!("any must be a list of facet mappings"));
+                };
+                for group in groups {
+                    let Value::Object(facets) = group else {
+                        return Err(anyhow!("any must be a list of facet mappings"));
+                    };
+                    let facets: Vec<(&String, &Value)> = facets.iter().collect();
+                    and.push(or_clause(&facets, resolver)?);
+                }
+            }
+            "exclude" => {
+                let Value::Object(facets) = value else {
+                    return Err(anyhow!("exclude must be a mapping of facet -> values"));
+                };
+                for (facet, values) in facets {
+                    add_facet(&mut exclude, facet, values, resolver)?;
+                }
+            }
+            other => match other.strip_prefix("exclude.") {
+                Some(facet) => add_facet(&mut exclude, facet, value, resolver)?,
+                None => and.push(or_clause(&[(key, value)], resolver)?),
+            },
+        }
+    }
+
+    if and.is_empty() {
+        return Err(anyhow!("targeting needs at least one included facet"));
+    }
+    let mut criteria = serde_json::json!({ "include": { "and": and } });
+    if !exclude.is_empty() {
+        criteria["exclude"] = serde_json::json!({ "or": exclude });
+    }
+    Ok(criteria)
+}
+
+fn or_clause(facets: &[(&String, &Value)], resolver: &mut Resolver) -> Result<Value> {
+    let mut or = serde_json::Map::new();
+    for (facet, values) in facets {
+        add_facet(&mut or, facet, values, resolver)?;
+    }
+    Ok(serde_json::json!({ "or": or }))
+}
+
+fn add_facet(
+    clause: &mut serde_json::Map<String, Value>,
+    facet: &str,
+    values: &Value,
+    resolver: &mut Resolver,
+) -> Result<()> {
+    let names: Vec<String> = match values {
+        Value::Array(items) => items.iter().map(scalar).collect::<Result<_>>()?,
+        other => vec![scalar(other)?],
+    };
+    if names.is_empty() {
+        return Err(anyhow!("facet {facet} has no values"));
+    }
+    let urns = names
+        .iter()
+        .map(|name| resolver.resolve(facet, name))
+        .collect::<Result<Vec<_>>>()?;
+    let entry = clause
+        .entry(facet_urn(facet))
+        .or_insert_with(|| Value::Array(Vec::new()));
+    if let Value::Array(existing) = entry {
+        for urn in urns {
+            if !existing.iter().any(|v| v.as_str() == Some(urn.as_str())) {
+                existing.push(Value::String(urn));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn scalar(value: &Value) -> Result<String> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Number(n) => Ok(n.to_string()),
+        other => Err(anyhow!("expected a name or URN, got {other}")),
+    }
+}
+
+/// Every entity URN in `targetingCriteria`, in order of appearance.
+pub fn criteria_urns(criteria: &Value) -> Vec<String> {
+    let mut urns = Vec::new();
+    for clause in include_clauses(criteria)
+        .into_iter()
+        .chain(exclude_clause(criteria))
+    {
+        for values in clause.values() {
+            for urn in values
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|v| v.as_str())
+            {
+                if !urns.iter().any(|u| u == urn) {
+                    urns.push(urn.to_string());
+                }
+            }
+        }
+    }
+    urns
+}
+
+/// Render `targetingCriteria` as an indented include/exclude tree, naming entities
+/// from `names` where known.
+pub fn explain(criteria: &Value, names: &BTreeMap<String, String>) -> String {
+    let mut out = String::new();
+    let and = include_clauses(criteria);
+    out.push_str("include: all of\n");
+    if and.is_empty() {
+        out.push_str("  (everyone)\n");
+    }
+    for or in and {
+        if or.len() == 1 {
+            explain_facets(&mut out, or, names, 1);
+        } else {
+            out.push_str("  any of\n");
+            explain_facets(&mut out, or, names, 2);
+        }
+    }
+    if let Some(or) = exclude_clause(criteria) {
+        out.push_str("exclude: any of\n");
+        explain_facets(&mut out, or, names, 1);
+    }
+    out
+}
+
+fn explain_facets(
+    out: &mut String,
+    or: &serde_json::Map<String, Value>,
+    names: &BTreeMap<String, String>,
+    depth: usize,
+) {
+    let indent = "  ".repeat(depth);
+    for (facet, values) in or {
+        let facet = facet.strip_prefix(FACET_URN).unwrap_or(facet);
+        out.push_str(&format!(
+            "{indent}{} (any of)\n",
+            facet.trim_start_matches(':')
+        ));
+        for urn in values
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|v| v.as_str())
+        {
+            match names.get(urn) {
+                Some(name) => out.push_str(&format!("{indent}  - {name} ({urn})\n")),
+                None => out.push_str(&format!("{indent}  - {urn}\n")),
+            }
+        }
+    }
+}
+
+/// The `or` maps ANDed under `include`.
+fn include_clauses(criteria: &Value) -> Vec<&serde_json::Map<String, Value>> {
+    criteria
+        .pointer("/include/and")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|clause| clause.get("or")?.as_object())
+        .collect()
+}
+
+fn exclude_clause(criteria: &Value) -> Option<&serde_json::Map<String, Value>> {
+    criteria.pointer("/exclude/or").and_then(|v| v.as_object())
+}
+
+fn parse_entities(body: &Value) -> Vec<Entity> {
+    body.get("elements")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|e| {
+            Some(Entity {
+                urn: e.get("urn")?.as_str()?.to_string(),
+                name: e.get("name")?.as_str()?.to_string(),
+                facet: e
+                    .get("facetUrn")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::TunnelMode;
+    use serde_json::json;
+
+    /// URN values resolve without API calls; the client is never used.
+    fn client() -> RestliClient {
+        RestliClient::new(
+            "http://127.0.0.1:9".to_string(),
+            "202601".to_string(),
+            "token".to_string(),
+            "2.0.0".to_string(),
+            Some(1),
+            TunnelMode::Auto,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn compiles_include_any_and_exclude() {
+        let client = client();
+        let mut resolver = Resolver::new(&client);
+        let spec = json!({
+            "locations": ["urn:li:geo:1", "urn:li:geo:1"],
+            "any": [{ "job_functions": "urn:li:function:8", "titles": "urn:li:title:9" }],
+            "exclude": { "companies": "urn:li:organization:3" },
+            "exclude.seniorities": ["urn:li:seniority:1"],
+        });
+        let criteria = compile(&spec, &mut resolver).unwrap();
+        assert_eq!(
+            criteria,
+            json!({
+                "include": { "and": [
+                    { "or": {
+                        "urn:li:adTargetingFacet:jobFunctions": ["urn:li:function:8"],
+                        "urn:li:adTargetingFacet:titles": ["urn:li:title:9"],
+                    } },
+                    { "or": { "urn:li:adTargetingFacet:locations": ["urn:li:geo:1"] } },
+                ] },
+                "exclude": { "or": {
+                    "urn:li:adTargetingFacet:employers": ["urn:li:organization:3"],
+                    "urn:li:adTargetingFacet:seniorities": ["urn:li:seniority:1"],
+                } },
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_specs_without_includes_or_values() {
+        let client = client();
+        let mut resolver = Resolver::new(&client);
+        let only_exclude = json!({ "exclude": { "titles": "urn:li:title:1" } });
+        assert!(compile(&only_exclude, &mut resolver).is_err());
+        assert!(compile(&json!({ "titles": [] }), &mut resolver).is_err());
+        assert!(compile(&json!({ "titles": [{ "x": 1 }] }), &mut resolver).is_err());
+        assert!(compile(&json!(["urn:li:title:1"]), &mut resolver).is_err());
+    }
+
+    #[test]
+    fn rejects_names_shared_by_several_entities() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", server.server_addr());
+        let entities = json!({ "elements": [
+            { "urn": "urn:li:organization:1", "name": "Acme" },
+            { "urn": "urn:li:organization:2", "name": "acme" },
+            { "urn": "urn:li:organization:3", "name": "Acme Labs" },
+        ] });
+        let api = std::thread::spawn(move || {
+            for request in server.incoming_requests().take(1) {
+                assert!(request.url().starts_with("/adTargetingEntities?"));
+                let response = tiny_http::Response::from_string(entities.to_string());
+                request.respond(response).unwrap();
+            }
+        });
+        let client = RestliClient::new(
+            base_url,
+            "202601".to_string(),
+            "token".to_string(),
+            "2.0.0".to_string(),
+            Some(5),
+            TunnelMode::Auto,
+        )
+        .unwrap();
+        let mut resolver = Resolver {
+            client: &client,
+            cache: EntityCache::default(),
+            cache_path: None,
+            dirty: false,
+        };
+
+        let err = resolver
+            .resolve("companies", "ACME")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("urn:li:organization:1"), "{err}");
+        assert!(err.contains("urn:li:organization:2"), "{err}");
+        let urn = resolver.resolve("companies", "acme labs").unwrap();
+        assert_eq!(urn, "urn:li:organization:3");
+        api.join().unwrap();
+
+        let cached = &resolver.cache.facets["urn:li:adTargetingFacet:employers"];
+        assert!(!cached.contains_key("acme"));
+        assert!(cached.contains_key("acme labs"));
+    }
+}
@@ -0,0 +1,118 @@
+use anyhow::{Result, anyhow};
+
+/// Parse a timestamp into epoch millis. Accepts epoch seconds or millis, `YYYY-MM-DD`,
+/// and `YYYY-MM-DD[T ]HH:MM[:SS[.fff]][Z|±HH:MM]` (UTC when no offset is given).
+pub fn parse_millis(value: &str) -> Result<u64> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(anyhow!("empty timestamp"));
+    }
+    if value.chars().all(|c| c.is_ascii_digit()) {
+        let n: u64 = value.parse()?;
+        // Anything below ~1973 in millis is treated as seconds.
+        return Ok(if n < 100_000_000_000 { n * 1000 } else { n });
+    }
+
+    let invalid = || anyhow!("invalid timestamp: {value}");
+    let (date, time) = match value.find(['T', ' ']) {
+        Some(i) => (&value[..i], Some(&value[i + 1..])),
+        None => (value, None),
+    };
+
+    let mut date_parts = date.split('-');
+    let year: i64 = number(date_parts.next()).ok_or_else(invalid)?;
+    let month: u32 = number(date_parts.next()).ok_or_else(invalid)?;
+    let day: u32 = number(date_parts.next()).ok_or_else(invalid)?;
+    if date_parts.next().is_some() || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return Err(invalid());
+    }
+
+    let mut seconds = days_from_civil(year, month, day) * 86_400;
+    let mut millis = 0i64;
+    if let Some(time) = time {
+        let (clock, offset_secs) = split_offset(time).ok_or_else(invalid)?;
+        let mut clock_parts = clock.split(':');
+        let hour: i64 = number(clock_parts.next()).ok_or_else(invalid)?;
+        let minute: i64 = number(clock_parts.next()).ok_or_else(invalid)?;
+        let (second, fraction) = match clock_parts.next() {
+            Some(sec) => {
+                let (sec, fraction) = sec.split_once('.').unwrap_or((sec, ""));
+                (number(Some(sec)).ok_or_else(invalid)?, fraction)
+            }
+            None => (0, ""),
+        };
+        if fraction.chars().any(|c| !c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let frac3: String = fraction.chars().chain("000".chars()).take(3).collect();
+        millis = frac3.parse::<i64>()?;
+        seconds += hour * 3600 + minute * 60 + second - offset_secs;
+    }
+
+    let total = seconds * 1000 + millis;
+    u64::try_from(total).map_err(|_| invalid())
+}
+
+pub fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Epoch millis -> `YYYY-MM-DDTHH:MM:SS.sssZ`.
+pub fn format_millis(millis: u64) -> String {
+    let secs = (millis / 1000) as i64;
+    let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+    let rem = secs.rem_euclid(86_400);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60,
+        millis % 1000
+    )
+}
+
+fn number<T: std::str::FromStr>(part: Option<&str>) -> Option<T> {
+    part.and_then(|p| p.parse().ok())
+}
+
+fn split_offset(time: &str) -> Option<(&str, i64)> {
+    if let Some(clock) = time.strip_suffix('Z') {
+        return Some((clock, 0));
+    }
+    if let Some(i) = time.rfind(['+', '-']) {
+        let (clock, offset) = time.split_at(i);
+        let sign = if offset.starts_with('-') { -1 } else { 1 };
+        let (h, m) = offset[1..].split_once(':').unwrap_or((&offset[1..], "0"));
+        let secs = h.parse::<i64>().ok()? * 3600 + m.parse::<i64>().ok()? * 60;
+        return Some((clock, sign * secs));
+    }
+    Some((time, 0))
+}
+
+// Howard Hinnant's days_from_civil.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let m = month as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+// Howard Hinnant's civil_from_days.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
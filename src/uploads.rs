@@ -15,6 +15,19 @@ pub struct FileParam {
     _temp: Option<tempfile::TempPath>,
 }
 
+impl FileParam {
+    /// `<input><suffix>` next to a local input; downloaded inputs (URL, S3) have no
+    /// local directory, so `<file name><suffix>` in the current directory.
+    pub fn sibling_path(&self, suffix: &str) -> PathBuf {
+        if self._temp.is_some() {
+            return PathBuf::from(format!("{}{suffix}", self.file_name));
+        }
+        let mut path = self.path.clone().into_os_string();
+        path.push(suffix);
+        PathBuf::from(path)
+    }
+}
+
 pub fn resolve_file_source(value: &str) -> Result<FileParam> {
     if value.starts_with("s3://") {
         return download_s3(value);
@@ -54,7 +67,7 @@ fn download_http(url: &str) -> Result<FileParam> {
     let path = temp_path.to_path_buf();
     let file_name = url
         .split('/')
-        .last()
+        .next_back()
         .filter(|v| !v.is_empty())
         .unwrap_or("download")
         .to_string();
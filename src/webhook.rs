@@ -0,0 +1,252 @@
+use anyhow::{Context, Result, anyhow};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::client::RestliClient;
+use crate::hashing::{hmac_sha256_hex, verify_hmac_sha256};
+use crate::leads::Flattener;
+use crate::timeutil::{format_millis, now_millis};
+
+pub const SIGNATURE_HEADER: &str = "X-LI-Signature";
+
+/// Where decoded notifications go: one JSON line each.
+pub enum Emitter {
+    Stdout,
+    File(PathBuf),
+    /// Shell command run once per notification with the JSON on stdin.
+    Exec(String),
+}
+
+impl Emitter {
+    fn emit(&self, event: &Value) -> Result<()> {
+        match self {
+            Self::Stdout => {
+                let mut out = std::io::stdout().lock();
+                writeln!(out, "{event}")?;
+                out.flush()?;
+            }
+            Self::File(path) => {
+                if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+                    fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+                }
+                let mut file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .with_context(|| format!("open {}", path.display()))?;
+                writeln!(file, "{event}").with_context(|| format!("write {}", path.display()))?;
+            }
+            Self::Exec(command) => {
+                let mut child = Command::new("sh")
+                    .arg("-c")
+                    .arg(command)
+                    .stdin(Stdio::piped())
+                    .spawn()
+                    .with_context(|| format!("spawn {command}"))?;
+                if let Some(mut stdin) = child.stdin.take() {
+                    writeln!(stdin, "{event}")?;
+                }
+                let status = child.wait()?;
+                if !status.success() {
+                    return Err(anyhow!("{command} exited with {status}"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct ServeOptions {
+    pub secret: String,
+    pub path: Option<String>,
+    pub emitter: Emitter,
+}
+
+/// Validation challenge answer: HMAC-SHA256 of the challenge code, keyed with the
+/// app's client secret.
+pub fn challenge_response(secret: &str, code: &str) -> Value {
+    serde_json::json!({
+        "challengeCode": code,
+        "challengeResponse": hmac_sha256_hex(secret.as_bytes(), code.as_bytes()),
+    })
+}
+
+/// `X-LI-Signature` is the hex HMAC-SHA256 of the raw body, optionally prefixed with
+/// `hmacsha256=`.
+pub fn verify_signature(secret: &str, body: &[u8], header: &str) -> bool {
+    let signature = header.strip_prefix("hmacsha256=").unwrap_or(header);
+    verify_hmac_sha256(secret.as_bytes(), body, signature)
+}
+
+/// Serve until the process is stopped. With `client`, lead notifications are enriched
+/// with the full lead response.
+pub fn serve(addr: &str, opts: &ServeOptions, client: Option<&RestliClient>) -> Result<()> {
+    let server = Server::http(addr).map_err(|err| anyhow!("listen on {addr}: {err}"))?;
+    log::info!("webhook listening on http://{addr}");
+    let mut flattener = client.map(Flattener::new);
+    for request in server.incoming_requests() {
+        if let Err(err) = handle(request, opts, client, flattener.as_mut()) {
+            log::error!("webhook request failed: {err:#}");
+        }
+    }
+    Ok(())
+}
+
+fn handle(
+    mut request: Request,
+    opts: &ServeOptions,
+    client: Option<&RestliClient>,
+    flattener: Option<&mut Flattener>,
+) -> Result<()> {
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    if opts.path.as_deref().is_some_and(|p| p != path) {
+        return respond(request, 404, &serde_json::json!({ "error": "not found" }));
+    }
+
+    let challenge = query_param(query, "challengeCode");
+    match (request.method(), challenge) {
+        (Method::Get, Some(code)) => {
+            log::info!("answering webhook validation challenge");
+            let body = challenge_response(&opts.secret, &code);
+            respond(request, 200, &body)
+        }
+        (Method::Post, _) => {
+            let mut body = Vec::new();
+            request
+                .as_reader()
+                .read_to_end(&mut body)
+                .context("read request body")?;
+            let signature = request
+                .headers()
+                .iter()
+                .find(|h| h.field.equiv(SIGNATURE_HEADER))
+                .map(|h| h.value.as_str().to_string());
+            if !signature.is_some_and(|s| verify_signature(&opts.secret, &body, &s)) {
+                log::warn!("rejected notification with missing or invalid signature");
+                return respond(
+                    request,
+                    401,
+                    &serde_json::json!({ "error": "bad signature" }),
+                );
+            }
+            let payload: Value = match serde_json::from_slice(&body) {
+                Ok(payload) => payload,
+                Err(err) => {
+                    return respond(
+                        request,
+                        400,
+                        &serde_json::json!({ "error": err.to_string() }),
+                    );
+                }
+            };
+            // Acknowledge first so slow enrichment or sinks do not trigger redelivery.
+            respond(request, 200, &serde_json::json!({}))?;
+
+            let notifications = match payload {
+                Value::Array(items) => items,
+                other => vec![other],
+            };
+            let mut flattener = flattener;
+            for notification in notifications {
+                let event = decode(notification, client, flattener.as_deref_mut());
+                opts.emitter.emit(&event)?;
+            }
+            Ok(())
+        }
+        _ => respond(
+            request,
+            405,
+            &serde_json::json!({ "error": "expected GET ?challengeCode= or POST" }),
+        ),
+    }
+}
+
+/// Wrap a notification with its receive time and, for lead notifications, the
+/// flattened lead response when enrichment is on.
+fn decode(
+    notification: Value,
+    client: Option<&RestliClient>,
+    flattener: Option<&mut Flattener>,
+) -> Value {
+    let mut event = serde_json::json!({
+        "received_at": format_millis(now_millis()),
+        "type": notification.get("type").cloned().unwrap_or(Value::Null),
+        "notification": notification,
+    });
+    let response_urn = event["notification"]
+        .get("leadGenFormResponse")
+        .and_then(|v| v.as_str())
+        .map(str::to_string);
+    if let (Some(client), Some(flattener), Some(urn)) = (client, flattener, response_urn) {
+        let id = urn.rsplit(':').next().unwrap_or(&urn);
+        match client.call(
+            "GET",
+            &format!("/leadFormResponses/{}", urlencoding::encode(id)),
+            &BTreeMap::new(),
+            &BTreeMap::new(),
+            None,
+        ) {
+            Ok(resp) => event["lead"] = flattener.flatten(&resp.body),
+            Err(err) => {
+                log::warn!("enrich {urn} failed: {err}");
+                event["enrich_error"] = Value::String(err.to_string());
+            }
+        }
+    }
+    event
+}
+
+fn query_param(query: &str, name: &str) -> Option<String> {
+    query.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        (key == name).then(|| {
+            urlencoding::decode(value)
+                .map(|v| v.into_owned())
+                .unwrap_or_else(|_| value.to_string())
+        })
+    })
+}
+
+fn respond(request: Request, status: u16, body: &Value) -> Result<()> {
+    let header = Header::from_bytes("Content-Type", "application/json")
+        .map_err(|_| anyhow!("invalid header"))?;
+    request
+        .respond(
+            Response::from_string(body.to_string())
+                .with_status_code(status)
+                .with_header(header),
+        )
+        .context("send response")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &[u8] = br#"{"type":"LEAD_ACTION"}"#;
+    const SIGNATURE: &str = "b552cee1f18f8d2fff0cf89f31fb4eb53bffc2af5344e64bbd6ce9dcde164cda";
+
+    #[test]
+    fn accepts_bare_and_prefixed_signatures() {
+        assert!(verify_signature("topsecret", BODY, SIGNATURE));
+        assert!(verify_signature(
+            "topsecret",
+            BODY,
+            &format!("hmacsha256={SIGNATURE}")
+        ));
+    }
+
+    #[test]
+    fn rejects_wrong_secret_body_or_garbage() {
+        assert!(!verify_signature("other", BODY, SIGNATURE));
+        assert!(!verify_signature("topsecret", b"{}", SIGNATURE));
+        assert!(!verify_signature("topsecret", BODY, "not-hex"));
+        assert!(!verify_signature("topsecret", BODY, ""));
+    }
+}